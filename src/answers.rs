@@ -0,0 +1,82 @@
+/// answers accepted on adventofcode.com for the inputs in data/ as (day, part, answer)
+pub const ANSWERS: &[(u8, u8, &str)] = &[
+    (1, 1, "1151792"),
+    (1, 2, "21790168"),
+    (2, 1, "383"),
+    (2, 2, "436"),
+    (3, 1, "170778545"),
+    (3, 2, "82868252"),
+    (4, 1, "2524"),
+    (4, 2, "1873"),
+    (5, 1, "7198"),
+    (5, 2, "4230"),
+    (6, 1, "4988"),
+    (6, 2, "1697"),
+    (7, 1, "465126289353"),
+    (7, 2, "70597497486371"),
+    (8, 1, "256"),
+    (8, 2, "1005"),
+    (9, 1, "6401092019345"),
+    (9, 2, "6431472344710"),
+    (10, 1, "776"),
+    (10, 2, "1657"),
+];
+
+pub fn answer(day: u8, part: u8) -> Option<&'static str> {
+    ANSWERS
+        .iter()
+        .find(|(d, p, _)| (*d, *p) == (day, part))
+        .map(|(_, _, a)| *a)
+}
+
+/// The final star is given for free once every other star has been collected.
+pub fn is_solved(day: u8, part: u8) -> bool {
+    match (day, part) {
+        (25, 2) => (1..=25).all(|d| (1..=2).all(|p| (d, p) == (25, 2) || is_solved(d, p))),
+        (d, p) => answer(d, p).is_some(),
+    }
+}
+
+/// how many of the 2 stars have been collected for each day
+pub fn stars_by_day() -> Vec<(u8, usize)> {
+    (1..=25)
+        .map(|day| (day, (1..=2).filter(|part| is_solved(day, *part)).count()))
+        .collect()
+}
+
+/// the stars collected for each day drawn as the 50 star calendar, with the total at the end
+pub fn summary() -> String {
+    let stars_by_day = stars_by_day();
+    let total: usize = stars_by_day.iter().map(|(_, stars)| stars).sum();
+    let rows: Vec<String> = stars_by_day
+        .iter()
+        .map(|(day, stars)| {
+            format!(
+                "day {day:02} {}{}",
+                "*".repeat(*stars),
+                ".".repeat(2usize.saturating_sub(*stars))
+            )
+        })
+        .collect();
+    format!("{}\n{total}/50 stars", rows.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn summary_test() {
+        let summary = summary();
+        assert_eq!(summary.lines().count(), 26);
+        assert_eq!(summary.lines().next(), Some("day 01 **"));
+        assert!(summary.lines().nth(24).unwrap().starts_with("day 25 "));
+        assert!(summary.lines().last().unwrap().ends_with("/50 stars"));
+    }
+    #[test]
+    fn stars_by_day_test() {
+        let stars = stars_by_day();
+        assert_eq!(stars.len(), 25);
+        assert_eq!(stars[0], (1, 2));
+        assert!(stars.iter().all(|(_, s)| *s <= 2));
+    }
+}
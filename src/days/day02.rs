@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 use crate::error;
 
 use nom::{
@@ -7,91 +9,171 @@ use nom::{
     IResult,
 };
 
-#[derive(Debug, Copy, Clone)]
-enum Order {
-    Initialised(i32),
-    Undefined,
-    Increasing(i32),
-    Decreasing(i32),
-    Failed,
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Violation {
+    /// the levels go the other way to the first step of the report
+    WrongDirection,
+    StepTooLarge,
+    StepTooSmall,
+    /// two levels in a row are the same
+    Plateau,
 }
 
-impl Order {
-    fn next_order(&self, i: i32) -> Order {
-        match self {
-            Order::Undefined => Order::Initialised(i),
-            Order::Initialised(n) => {
-                if (n + 1..=n + 3).contains(&i) {
-                    Order::Increasing(i)
-                } else if (n - 3..=n - 1).contains(&i) {
-                    Order::Decreasing(i)
-                } else {
-                    Order::Failed
-                }
-            }
-            Order::Failed => Order::Failed,
-            Order::Increasing(n) => {
-                if (n + 1..=n + 3).contains(&i) {
-                    Order::Increasing(i)
-                } else {
-                    Order::Failed
-                }
-            }
-            Order::Decreasing(n) => {
-                if (n - 3..=n - 1).contains(&i) {
-                    Order::Decreasing(i)
-                } else {
-                    Order::Failed
-                }
-            }
-        }
-    }
+/// why a report is unsafe, `at` is the index of the level the bad step goes into
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Unsafe {
+    at: usize,
+    violation: Violation,
+}
+
+struct Dampener {
+    /// how far apart two levels in a row can be, in either direction
+    steps: RangeInclusive<i32>,
+    /// how many levels can be removed to make a report safe
+    removals: usize,
 }
 
+const STEPS: RangeInclusive<i32> = 1..=3;
+
 fn numbers(s: &str) -> IResult<&str, Vec<i32>> {
     separated_list1(space1, map_res(digit1, str::parse::<i32>))(s)
 }
 
-fn passes(n: &Vec<i32>) -> bool {
-    match n.iter().fold(Order::Undefined, |acc, i| acc.next_order(*i)) {
-        Order::Failed => false,
-        _ => true,
+impl Dampener {
+    /// Walks the report once, ignoring the levels at the indices in `removed`, and stops at the
+    /// first bad step.
+    fn check(&self, levels: &[i32], removed: &[usize]) -> Result<(), Unsafe> {
+        let mut kept = (0..levels.len()).filter(|i| !removed.contains(i));
+        let Some(mut previous) = kept.next() else {
+            return Ok(());
+        };
+        let mut increasing: Option<bool> = None;
+        for at in kept {
+            let step = levels[at] - levels[previous];
+            let violation = if step == 0 {
+                Some(Violation::Plateau)
+            } else if *increasing.get_or_insert(step > 0) != (step > 0) {
+                Some(Violation::WrongDirection)
+            } else if step.abs() > *self.steps.end() {
+                Some(Violation::StepTooLarge)
+            } else if step.abs() < *self.steps.start() {
+                Some(Violation::StepTooSmall)
+            } else {
+                None
+            };
+            if let Some(violation) = violation {
+                return Err(Unsafe { at, violation });
+            }
+            previous = at;
+        }
+        Ok(())
+    }
+
+    /// Only removing a level that makes up the bad step can fix it, or for a wrong direction
+    /// one of the first two levels as they decide which direction is right.
+    fn candidates(levels: &[i32], removed: &[usize], problem: Unsafe) -> Vec<usize> {
+        let kept: Vec<usize> = (0..levels.len()).filter(|i| !removed.contains(i)).collect();
+        let at = kept.iter().position(|i| *i == problem.at).unwrap();
+        let mut candidates = vec![kept[at], kept[at - 1]];
+        if problem.violation == Violation::WrongDirection {
+            candidates.extend(kept.iter().take(2));
+        }
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+
+    fn dampen_from(&self, levels: &[i32], removed: &mut Vec<usize>) -> Result<(), Unsafe> {
+        let problem = match self.check(levels, removed) {
+            Ok(()) => return Ok(()),
+            Err(problem) => problem,
+        };
+        if removed.len() == self.removals {
+            return Err(problem);
+        }
+        for candidate in Dampener::candidates(levels, removed, problem) {
+            removed.push(candidate);
+            if self.dampen_from(levels, removed).is_ok() {
+                return Ok(());
+            }
+            removed.pop();
+        }
+        Err(problem)
     }
-}
 
-fn any_passes(n: &Vec<i32>) -> bool {
-    if passes(n) {
-        true
-    } else {
-        (0..n.len())
-            .into_iter()
-            .map(|i| {
-                n.iter()
-                    .enumerate()
-                    .filter(|(idx, _)| *idx != i)
-                    .map(|(_, elem)| *elem)
-                    .collect::<Vec<i32>>()
-            })
-            .any(|ns| passes(&ns))
+    /// The indices of the levels removed to make the report safe, empty if it already was,
+    /// otherwise the first problem with the report as it is.
+    fn dampen(&self, levels: &[i32]) -> Result<Vec<usize>, Unsafe> {
+        let mut removed = Vec::new();
+        self.dampen_from(levels, &mut removed)?;
+        removed.sort();
+        Ok(removed)
     }
 }
 
-pub fn part1(input: String) -> Result<String, error::Error> {
-    let count: usize = input
+fn safe_count(input: &str, dampener: &Dampener) -> usize {
+    input
         .lines()
         .map(|ln| numbers(ln).unwrap().1)
-        .filter(passes)
-        .count();
-    Ok(count.to_string())
+        .filter(|levels| dampener.dampen(levels).is_ok())
+        .count()
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let dampener = Dampener {
+        steps: STEPS,
+        removals: 0,
+    };
+    Ok(safe_count(&input, &dampener).to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let count: usize = input
+    let dampener = Dampener {
+        steps: STEPS,
+        removals: 1,
+    };
+    Ok(safe_count(&input, &dampener).to_string())
+}
+
+/// Says whether each report is safe, what is wrong with it if not, and which levels the
+/// dampener removed.
+///
+/// --removals <n>        levels the dampener can remove, 1 by default
+/// --steps <min>-<max>   the tolerated step between levels, 1-3 by default
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let mut dampener = Dampener {
+        steps: STEPS,
+        removals: 1,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().map(String::as_str);
+        let invalid = || error::Error::InvalidArgument(format!("{arg} {}", value.unwrap_or("")));
+        match arg.as_str() {
+            "--removals" => {
+                dampener.removals = value.and_then(|v| v.parse().ok()).ok_or_else(invalid)?
+            }
+            "--steps" => {
+                dampener.steps = value
+                    .and_then(|v| v.split_once('-'))
+                    .and_then(|(min, max)| Some(min.parse().ok()?..=max.parse().ok()?))
+                    .ok_or_else(invalid)?
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(input
         .lines()
-        .map(|ln| numbers(ln).unwrap().1)
-        .filter(any_passes)
-        .count();
-    Ok(count.to_string())
+        .map(|ln| {
+            let levels = numbers(ln).unwrap().1;
+            match dampener.dampen(&levels) {
+                Ok(removed) if removed.is_empty() => format!("{ln}: safe"),
+                Ok(removed) => format!("{ln}: safe removing levels {removed:?}"),
+                Err(Unsafe { at, violation }) => format!("{ln}: {violation:?} at level {at}"),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
 }
 
 #[cfg(test)]
@@ -120,4 +202,61 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "6")
     }
+    #[test]
+    fn test_violations() {
+        let strict = Dampener {
+            steps: STEPS,
+            removals: 0,
+        };
+        let unsafe_at = |levels: &[i32]| strict.dampen(levels).unwrap_err();
+        assert_eq!(
+            unsafe_at(&[1, 2, 7, 8, 9]),
+            Unsafe {
+                at: 2,
+                violation: Violation::StepTooLarge
+            }
+        );
+        assert_eq!(
+            unsafe_at(&[1, 3, 2, 4, 5]),
+            Unsafe {
+                at: 2,
+                violation: Violation::WrongDirection
+            }
+        );
+        assert_eq!(
+            unsafe_at(&[8, 6, 4, 4, 1]),
+            Unsafe {
+                at: 3,
+                violation: Violation::Plateau
+            }
+        );
+        let wide = Dampener {
+            steps: 2..=5,
+            removals: 0,
+        };
+        assert_eq!(
+            wide.dampen(&[1, 2, 7]).unwrap_err().violation,
+            Violation::StepTooSmall
+        );
+        assert_eq!(wide.dampen(&[1, 3, 8]), Ok(vec![]));
+    }
+    #[test]
+    fn test_dampener() {
+        let dampener = Dampener {
+            steps: STEPS,
+            removals: 1,
+        };
+        assert_eq!(dampener.dampen(&[7, 6, 4, 2, 1]), Ok(vec![]));
+        assert_eq!(dampener.dampen(&[1, 3, 2, 4, 5]), Ok(vec![1]));
+        assert_eq!(dampener.dampen(&[8, 6, 4, 4, 1]), Ok(vec![2]));
+        // only removing the first level fixes the direction
+        assert_eq!(dampener.dampen(&[5, 6, 4, 3, 2]), Ok(vec![0]));
+        assert!(dampener.dampen(&[9, 7, 6, 2, 1]).is_err());
+        let twice = Dampener {
+            steps: STEPS,
+            removals: 2,
+        };
+        assert_eq!(twice.dampen(&[1, 9, 2, 9, 3]), Ok(vec![1, 3]));
+        assert!(dampener.dampen(&[1, 9, 2, 9, 3]).is_err());
+    }
 }
@@ -1,96 +1,224 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead},
+};
+
 use crate::error;
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::{anychar, digit1},
-    combinator::{map, map_res},
-    multi::{many1, many_till},
-    sequence::{delimited, separated_pair},
+    bytes::complete::{tag, take_while_m_n},
+    combinator::map_res,
+    multi::separated_list0,
+    sequence::terminated,
     IResult,
 };
-#[derive(Debug, Copy, Clone)]
-enum UncorruptedMemory {
-    Mul(i32, i32),
-    On(i32),
-    Off(i32),
-}
-
-impl UncorruptedMemory {
-    fn build_switches(s: &str) -> Result<UncorruptedMemory, nom::Err<&str>> {
-        match s {
-            "do()" => Ok(UncorruptedMemory::On(0)),
-            "don't()" => Ok(UncorruptedMemory::Off(0)),
-            _ => panic!("filter beffore build"),
-        }
+
+/// An instruction found in the corrupted memory, `offset` is the byte the name starts at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    offset: usize,
+    name: &'static str,
+    args: Vec<u64>,
+}
+
+impl Token {
+    fn call(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(u64::to_string).collect();
+        format!("{}({})", self.name, args.join(","))
+    }
+}
+
+#[derive(Debug)]
+struct Machine {
+    enabled: bool,
+    total: u64,
+}
+
+/// An instruction the scanner looks for, written as `name(args)` with the arguments being
+/// 1-3 digit numbers.
+trait Handler {
+    fn name(&self) -> &'static str;
+    fn arity(&self) -> usize;
+    fn execute(&self, args: &[u64], machine: &mut Machine);
+}
+
+struct Mul;
+struct Do;
+struct Dont;
+
+impl Handler for Mul {
+    fn name(&self) -> &'static str {
+        "mul"
     }
-    fn build_mul(p: (i32, i32)) -> Result<UncorruptedMemory, nom::Err<&'static str>> {
-        Ok(UncorruptedMemory::Mul(p.0, p.1))
+    fn arity(&self) -> usize {
+        2
     }
-    fn to_string(&self) -> String {
-        match *self {
-            UncorruptedMemory::Mul(i, j) => (i * j).to_string(),
-            UncorruptedMemory::On(i) => i.to_string(),
-            UncorruptedMemory::Off(i) => i.to_string(),
+    fn execute(&self, args: &[u64], machine: &mut Machine) {
+        if machine.enabled {
+            machine.total += args[0] * args[1];
         }
     }
 }
 
-fn filtered_inputs(s: &str) -> IResult<&str, Vec<UncorruptedMemory>> {
-    let multiplication_parser = delimited(
-        tag("mul("),
-        map_res(
-            separated_pair(
-                map_res(digit1, str::parse::<i32>),
-                tag(","),
-                map_res(digit1, str::parse::<i32>),
-            ),
-            UncorruptedMemory::build_mul,
-        ),
-        tag(")"),
-    );
-    let filter_parser = alt((
-        multiplication_parser,
-        map_res(tag("do()"), UncorruptedMemory::build_switches),
-        map_res(tag("don't()"), UncorruptedMemory::build_switches),
-    ));
-    many1(map(many_till(anychar, filter_parser), |(_, r)| r))(s)
-}
-fn inputs(s: &str) -> IResult<&str, Vec<(i32, i32)>> {
-    let multiplication_parser = delimited(
-        tag("mul("),
-        separated_pair(
-            map_res(digit1, str::parse::<i32>),
+impl Handler for Do {
+    fn name(&self) -> &'static str {
+        "do"
+    }
+    fn arity(&self) -> usize {
+        0
+    }
+    fn execute(&self, _args: &[u64], machine: &mut Machine) {
+        machine.enabled = true;
+    }
+}
+
+impl Handler for Dont {
+    fn name(&self) -> &'static str {
+        "don't"
+    }
+    fn arity(&self) -> usize {
+        0
+    }
+    fn execute(&self, _args: &[u64], machine: &mut Machine) {
+        machine.enabled = false;
+    }
+}
+
+type Handlers<'a> = [&'a dyn Handler];
+
+const PART1_HANDLERS: [&dyn Handler; 1] = [&Mul];
+const PART2_HANDLERS: [&dyn Handler; 3] = [&Mul, &Do, &Dont];
+
+/// the arguments and closing bracket following an opening bracket
+fn arguments(s: &str) -> IResult<&str, Vec<u64>> {
+    terminated(
+        separated_list0(
             tag(","),
-            map_res(digit1, str::parse::<i32>),
+            map_res(
+                take_while_m_n(1, 3, |c: char| c.is_ascii_digit()),
+                str::parse,
+            ),
         ),
         tag(")"),
-    );
-    many1(map(many_till(anychar, multiplication_parser), |(_, r)| r))(s)
+    )(s)
+}
+
+/// Every instruction in a line starting at byte `offset` of the memory. Only the opening
+/// brackets are visited, checking which instruction name comes right before each one.
+fn scan_line(line: &str, offset: usize, handlers: &Handlers) -> Vec<Token> {
+    line.match_indices('(')
+        .filter_map(|(open, _)| {
+            let handler = handlers.iter().find(|h| line[..open].ends_with(h.name()))?;
+            let (_, args) = arguments(&line[open + 1..]).ok()?;
+            (args.len() == handler.arity()).then(|| Token {
+                offset: offset + open - handler.name().len(),
+                name: handler.name(),
+                args,
+            })
+        })
+        .collect()
+}
+
+/// Reads the memory a line at a time, an instruction can't be split over lines as that
+/// would corrupt it, so any size of input can be streamed through.
+struct Scanner<'a, R: BufRead> {
+    reader: R,
+    handlers: &'a Handlers<'a>,
+    offset: usize,
+    line: String,
+    pending: VecDeque<Token>,
+}
+
+impl<'a, R: BufRead> Scanner<'a, R> {
+    fn new(reader: R, handlers: &'a Handlers<'a>) -> Scanner<'a, R> {
+        Scanner {
+            reader,
+            handlers,
+            offset: 0,
+            line: String::new(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Scanner<'_, R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<io::Result<Token>> {
+        while self.pending.is_empty() {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(read) => {
+                    self.pending
+                        .extend(scan_line(&self.line, self.offset, self.handlers));
+                    self.offset += read;
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Runs every instruction in the memory and returns the total, calling `trace` with each
+/// instruction and how much it added to the total.
+fn interpret(
+    memory: impl BufRead,
+    handlers: &Handlers,
+    mut trace: impl FnMut(&Token, u64),
+) -> io::Result<u64> {
+    let mut machine = Machine {
+        enabled: true,
+        total: 0,
+    };
+    for token in Scanner::new(memory, handlers) {
+        let token = token?;
+        let before = machine.total;
+        if let Some(handler) = handlers.iter().find(|h| h.name() == token.name) {
+            handler.execute(&token.args, &mut machine);
+        }
+        trace(&token, machine.total - before);
+    }
+    Ok(machine.total)
 }
 
 pub fn part1(input: String) -> Result<String, error::Error> {
-    let result: i32 = inputs(&input).unwrap().1.iter().map(|(x, y)| x * y).sum();
+    let result = interpret(input.as_bytes(), &PART1_HANDLERS, |_, _| ()).unwrap();
     Ok(result.to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let result = filtered_inputs(input.as_str()).unwrap().1.iter().fold(
-        UncorruptedMemory::On(0),
-        |acc, n| match (acc, *n) {
-            (UncorruptedMemory::On(i), UncorruptedMemory::Off(_)) => UncorruptedMemory::Off(i),
-            (UncorruptedMemory::On(i), UncorruptedMemory::Mul(x, y)) => {
-                UncorruptedMemory::On(i + (x * y))
-            }
-            (UncorruptedMemory::Off(i), UncorruptedMemory::On(_)) => UncorruptedMemory::On(i),
-            (UncorruptedMemory::Mul(_, _), _) => {
-                panic!("accumulator should only be On or Off: {:?}", &acc)
-            }
-            (x, _) => x,
-        },
-    );
+    let result = interpret(input.as_bytes(), &PART2_HANDLERS, |_, _| ()).unwrap();
     Ok(result.to_string())
 }
 
+/// Lists each instruction accepted with its byte offset and what it added to the total, using
+/// the part 2 instructions unless given `1`.
+///
+/// --stdin  stream the memory from stdin rather than reading data/day03.txt
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let mut handlers: &Handlers = &PART2_HANDLERS;
+    let mut stdin = false;
+    for arg in args {
+        match arg.as_str() {
+            "1" => handlers = &PART1_HANDLERS,
+            "2" => handlers = &PART2_HANDLERS,
+            "--stdin" => stdin = true,
+            x => return Err(error::Error::InvalidArgument(x.to_string())),
+        }
+    }
+    let print = |token: &Token, contribution: u64| {
+        println!("{:>8}: {} +{contribution}", token.offset, token.call())
+    };
+    // reading memory already in a string can't fail so only stdin has errors to report
+    let total = match stdin {
+        true => interpret(io::stdin().lock(), handlers, print),
+        false => interpret(input.as_bytes(), handlers, print),
+    }
+    .map_err(error::Error::StdinReadFailed)?;
+    Ok(format!("total {total}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,4 +239,45 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "48")
     }
+
+    #[test]
+    fn test_scan_line() {
+        let tokens = scan_line(
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(1234,5)do(1)",
+            10,
+            &PART2_HANDLERS,
+        );
+        let calls: Vec<(usize, String)> = tokens.iter().map(|t| (t.offset, t.call())).collect();
+        assert_eq!(
+            calls,
+            vec![
+                (11, "mul(2,4)".to_string()),
+                (30, "don't()".to_string()),
+                (38, "mul(5,5)".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64]
+(mul(11,8)undo()?mul(8,5))";
+        let mut trace = Vec::new();
+        let total = interpret(memory.as_bytes(), &PART2_HANDLERS, |t, c| {
+            trace.push(format!("{}: {} +{c}", t.offset, t.call()))
+        })
+        .unwrap();
+        assert_eq!(total, 48);
+        assert_eq!(
+            trace,
+            vec![
+                "1: mul(2,4) +8",
+                "20: don't() +0",
+                "28: mul(5,5) +0",
+                "49: mul(11,8) +0",
+                "60: do() +0",
+                "65: mul(8,5) +40"
+            ]
+        );
+    }
 }
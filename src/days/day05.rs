@@ -1,6 +1,6 @@
 use crate::error;
 
-use std::{collections::HashMap, iter};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use nom::{
     bytes::complete::tag, character::complete::digit1, combinator::map_res, multi::separated_list1,
@@ -19,109 +19,189 @@ fn book_pages(s: &str) -> IResult<&str, Vec<u32>> {
     separated_list1(tag(","), map_res(digit1, str::parse::<u32>))(s)
 }
 
+fn updates(input: &str) -> Vec<Vec<u32>> {
+    input
+        .lines()
+        .skip_while(|l| !l.is_empty())
+        .skip(1)
+        .map(book_pages)
+        .map(|r| r.unwrap().1)
+        .collect()
+}
+
+fn middle(pages: &[u32]) -> u32 {
+    pages[pages.len() / 2]
+}
+
+fn joined(pages: &[u32]) -> String {
+    pages
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 struct PageOrder {
-    order_map: HashMap<u32, Vec<u32>>,
+    /// the pages that each page has to be printed before
+    order_map: HashMap<u32, HashSet<u32>>,
 }
 
 impl PageOrder {
-    pub fn build_from_input(input: &String) -> PageOrder {
-        let order_map: HashMap<u32, Vec<u32>> = input
+    pub fn build_from_input(input: &str) -> PageOrder {
+        let mut order_map: HashMap<u32, HashSet<u32>> = HashMap::new();
+        for (before, after) in input
             .lines()
             .take_while(|l| !l.is_empty())
             .map(number_pair)
             .map(|r| r.unwrap().1)
-            .fold(HashMap::new(), |mut acc, x| {
-                acc.entry(x.0)
-                    .and_modify(|v| v.push(x.1))
-                    .or_insert(vec![x.1]);
-                acc
-            });
+        {
+            order_map.entry(before).or_default().insert(after);
+        }
         PageOrder { order_map }
     }
 
-    pub fn is_before(&self, prior_page: &u32, page: &u32) -> bool {
-        self.order_map.get(page).map_or(true, |must_be_beffore| {
-            !must_be_beffore.contains(prior_page)
-        })
+    fn after(&self, page: u32) -> impl Iterator<Item = u32> + '_ {
+        self.order_map.get(&page).into_iter().flatten().copied()
     }
 
-    pub fn is_ordered(&self, history: &Vec<u32>, page: &u32) -> bool {
-        history
+    /// every rule `before|after` that the update breaks by printing `after` first
+    pub fn violations(&self, pages: &[u32]) -> Vec<(u32, u32)> {
+        let position: HashMap<u32, usize> =
+            pages.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+        let mut violations: Vec<(u32, u32)> = pages
             .iter()
-            .all(|prior_page| self.is_before(prior_page, page))
+            .flat_map(|before| {
+                self.after(*before)
+                    .filter(|after| position.get(after).is_some_and(|a| a < &position[before]))
+                    .map(|after| (*before, after))
+            })
+            .collect();
+        violations.sort_by_key(|(before, after)| (position[before], position[after]));
+        violations
     }
 
-    pub fn insert(&self, history: &Vec<u32>, page: &u32) -> Vec<u32> {
-        let prior = history
-            .iter()
-            .take_while(|prior_page| self.is_before(*prior_page, page));
-        let i = iter::once(page);
-        let post = history
+    pub fn is_ordered(&self, pages: &[u32]) -> bool {
+        self.violations(pages).is_empty()
+    }
+
+    /// Topologically sorts the pages using only the rules between them, keeping pages in
+    /// their original order where the rules allow.
+    /// If the rules can't all be satisfied the pages of one of the cycles stopping them are
+    /// returned instead, in the order the rules put them.
+    pub fn reorder(&self, pages: &[u32]) -> Result<Vec<u32>, Vec<u32>> {
+        let mut blocked_by: HashMap<u32, usize> = pages.iter().map(|p| (*p, 0)).collect();
+        for before in pages {
+            for after in self.after(*before) {
+                if let Some(count) = blocked_by.get_mut(&after) {
+                    *count += 1;
+                }
+            }
+        }
+        let mut ready: VecDeque<u32> = pages
             .iter()
-            .skip_while(|prior_page| self.is_before(*prior_page, page));
-        prior.chain(i).chain(post).map(|x| *x).collect()
+            .filter(|p| blocked_by[*p] == 0)
+            .copied()
+            .collect();
+        let mut sorted = Vec::with_capacity(pages.len());
+        while let Some(page) = ready.pop_front() {
+            sorted.push(page);
+            for after in self.after(page) {
+                if let Some(count) = blocked_by.get_mut(&after) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push_back(after);
+                    }
+                }
+            }
+        }
+        if sorted.len() == pages.len() {
+            Ok(sorted)
+        } else {
+            let unsorted: Vec<u32> = pages
+                .iter()
+                .filter(|p| blocked_by[*p] > 0)
+                .copied()
+                .collect();
+            Err(self.cycle(&unsorted))
+        }
+    }
+
+    /// Every page left unsorted still has a rule from another unsorted page so walking back
+    /// through those rules has to come round to a page it has already been to.
+    fn cycle(&self, unsorted: &[u32]) -> Vec<u32> {
+        let mut path: Vec<u32> = vec![unsorted[0]];
+        loop {
+            let page = *path.last().unwrap();
+            let previous = *unsorted
+                .iter()
+                .find(|p| self.order_map.get(*p).is_some_and(|a| a.contains(&page)))
+                .unwrap();
+            if let Some(i) = path.iter().position(|p| *p == previous) {
+                let mut cycle = path.split_off(i);
+                cycle.reverse();
+                return cycle;
+            }
+            path.push(previous);
+        }
     }
 }
 
 pub fn part1(input: String) -> Result<String, error::Error> {
     let p = PageOrder::build_from_input(&input);
-
-    let middle_page_numbers: u32 = input
-        .lines()
-        .skip_while(|l| !l.is_empty())
-        .skip(1)
-        .map(book_pages)
-        .map(|r| r.unwrap().1)
-        .map(|pages| {
-            pages.iter().fold(
-                Some(Vec::<u32>::new()),
-                |previous_pages, page| match previous_pages {
-                    None => None,
-                    Some(history) => {
-                        if p.is_ordered(&history, page) {
-                            Some(
-                                history
-                                    .iter()
-                                    .chain(std::iter::once(page))
-                                    .map(|x| *x)
-                                    .collect(),
-                            )
-                        } else {
-                            None
-                        }
-                    }
-                },
-            )
-        })
-        .flat_map(|s| s)
-        .map(|s| s[s.len() / 2])
+    let middle_page_numbers: u32 = updates(&input)
+        .iter()
+        .filter(|pages| p.is_ordered(pages))
+        .map(|pages| middle(pages))
         .sum();
-
     Ok(middle_page_numbers.to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
     let p = PageOrder::build_from_input(&input);
+    let mut middle_page_numbers = 0;
+    for pages in updates(&input).iter().filter(|pages| !p.is_ordered(pages)) {
+        match p.reorder(pages) {
+            Ok(sorted) => middle_page_numbers += middle(&sorted),
+            Err(cycle) => {
+                return Err(error::Error::InvalidInput(format!(
+                    "update {} can't be ordered, the rules for pages {} form a cycle",
+                    joined(pages),
+                    joined(&cycle)
+                )))
+            }
+        }
+    }
+    Ok(middle_page_numbers.to_string())
+}
 
-    let middle_page_numbers: u32 = input
-        .lines()
-        .skip_while(|l| !l.is_empty())
-        .skip(1)
-        .map(book_pages)
-        .map(|r| r.unwrap().1)
+/// Lists every update that is out of order with how it should be reordered, or the pages
+/// making that impossible.
+///
+/// --violations  also list the rules each update breaks
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let violations = match args.first().map(String::as_str) {
+        Some("--violations") => true,
+        None => false,
+        Some(x) => return Err(error::Error::InvalidArgument(x.to_string())),
+    };
+    let p = PageOrder::build_from_input(&input);
+    Ok(updates(&input)
+        .iter()
+        .filter(|pages| !p.is_ordered(pages))
         .map(|pages| {
-            (
-                pages.clone(),
-                pages
-                    .iter()
-                    .fold(Vec::<u32>::new(), |history, page| p.insert(&history, &page)),
-            )
+            let mut line = match p.reorder(pages) {
+                Ok(sorted) => format!("{} -> {}", joined(pages), joined(&sorted)),
+                Err(cycle) => format!("{} has a cycle {}", joined(pages), joined(&cycle)),
+            };
+            if violations {
+                for (before, after) in p.violations(pages) {
+                    line.push_str(&format!("\n  breaks {before}|{after}"));
+                }
+            }
+            line
         })
-        .filter(|(original_pages, sorted_pages)| original_pages != sorted_pages)
-        .map(|(_, sorted_pages)| sorted_pages[sorted_pages.len() / 2])
-        .sum();
-
-    Ok(middle_page_numbers.to_string())
+        .collect::<Vec<String>>()
+        .join("\n"))
 }
 
 #[cfg(test)]
@@ -169,4 +249,36 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "123")
     }
+    #[test]
+    fn test_violations() {
+        let p = PageOrder::build_from_input(INPUT);
+        assert_eq!(p.violations(&[75, 97, 47, 61, 53]), vec![(97, 75)]);
+        assert_eq!(
+            p.violations(&[97, 13, 75, 29, 47]),
+            vec![(75, 13), (29, 13), (47, 13), (47, 29)]
+        );
+        assert!(p.violations(&[75, 47, 61, 53, 29]).is_empty());
+    }
+    #[test]
+    fn test_reorder() {
+        let p = PageOrder::build_from_input(INPUT);
+        assert_eq!(p.reorder(&[61, 13, 29]), Ok(vec![61, 29, 13]));
+        assert_eq!(
+            p.reorder(&[97, 13, 75, 29, 47]),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+    }
+    #[test]
+    fn test_cycle() {
+        let input = "1|2
+2|3
+3|1
+3|4
+
+4,3,2,1";
+        let p = PageOrder::build_from_input(input);
+        assert_eq!(p.reorder(&[4, 3, 2, 1]), Err(vec![1, 2, 3]));
+        assert_eq!(p.reorder(&[4, 3, 2]), Ok(vec![2, 3, 4]));
+        assert!(part2(input.to_string()).is_err());
+    }
 }
@@ -1,50 +1,18 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, BufRead},
+    thread,
+};
 
-use crate::error;
+use array2d::Array2D;
 
-#[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
-struct Point(usize, usize);
-impl Point {
-    fn next_point(&self, d: Direction, positions: &HashMap<Point, Space>) -> Option<Point> {
-        match (self, d) {
-            (point, Direction::East) => positions
-                .get_key_value(&Point(point.0, point.1 + 1))
-                .map(|(k, _)| *k),
-            (point, Direction::South) => positions
-                .get_key_value(&Point(point.0 + 1, point.1))
-                .map(|(k, _)| *k),
-            (point, Direction::West) => {
-                if point.1 <= 0 {
-                    None
-                } else {
-                    Some(Point(point.0, point.1.abs_diff(1)))
-                }
-            }
-            (point, Direction::North) => {
-                if point.0 <= 0 {
-                    None
-                } else {
-                    Some(Point(point.0.abs_diff(1), point.1))
-                }
-            }
-        }
-    }
-}
+use crate::{
+    error,
+    utils::{grid_from_input, ArrayUtils2D, Direction},
+};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Space {
-    Visited(HashMap<Direction, usize>),
-    Unseen,
-    Obstruction,
-}
-
-#[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
-enum NextSpace {
-    Clear,
-    Obstructed,
-    OutOfBounds,
-    Loopy,
-}
+type Point = (usize, usize);
 
 #[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
 struct Guard {
@@ -52,185 +20,279 @@ struct Guard {
     direction: Direction,
 }
 
-#[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn from_char(c: char) -> Direction {
-        match c {
-            '>' => Direction::East,
-            'v' => Direction::South,
-            '<' => Direction::West,
-            '^' => Direction::North,
-            x => panic!("unexpected input {}", x),
-        }
-    }
-    fn right_turn(&self) -> Direction {
-        match self {
-            Direction::North => Direction::East,
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-        }
-    }
-}
-impl Space {
-    fn from_char(c: char) -> Space {
-        match c {
-            '.' => Space::Unseen,
-            '#' => Space::Obstruction,
-            _ => Space::Visited(HashMap::from([(Direction::from_char(c), 1)])),
-        }
-    }
-}
-
 struct LabMap {
-    positions: HashMap<Point, Space>,
-    guard_location: Guard,
-    turns: usize,
+    grid: Array2D<char>,
+    limits: ArrayUtils2D,
+    guard: Guard,
+    /// for each direction, where the guard ends up walking from a point until it reaches an
+    /// obstruction, None if it walks off the map first
+    jumps: [Array2D<Option<Point>>; 4],
 }
-impl LabMap {
-    fn from_input(input: &String) -> LabMap {
-        let positions: HashMap<Point, Space> = input
-            .lines()
-            .enumerate()
-            .flat_map(|(i, ln)| {
-                ln.chars()
-                    .enumerate()
-                    .map(move |(j, c)| (Point(i, j), Space::from_char(c)))
-            })
-            .collect();
 
-        let guard_location: Guard = positions
-            .iter()
-            .filter_map(|x| {
-                if let (p, Space::Visited(m)) = x {
-                    let d = m.keys().next().unwrap();
-                    Some(Guard {
-                        point: *p,
-                        direction: *d,
-                    })
-                } else {
-                    None
-                }
+impl LabMap {
+    fn from_input(input: &str) -> LabMap {
+        let grid = grid_from_input(input);
+        let limits = ArrayUtils2D::from_array(&grid);
+        let guard = grid
+            .enumerate_row_major()
+            .find_map(|(point, c)| {
+                Direction::from_char(*c).map(|direction| Guard { point, direction })
             })
-            .next()
             .unwrap();
-
+        let jumps = Direction::ALL.map(|d| LabMap::jump_table(&grid, &limits, d));
         LabMap {
-            positions,
-            guard_location,
-            turns: 0,
+            grid,
+            limits,
+            guard,
+            jumps,
         }
     }
 
-    fn next_point(&self) -> Option<Point> {
-        self.guard_location
-            .point
-            .next_point(self.guard_location.direction, &self.positions)
+    fn jump_table(
+        grid: &Array2D<char>,
+        limits: &ArrayUtils2D,
+        d: Direction,
+    ) -> Array2D<Option<Point>> {
+        let mut table = Array2D::filled_with(None, grid.num_rows(), grid.num_columns());
+        // fill in the points nearest the edge the guard is walking towards first so the next
+        // point along is always known
+        let mut points = limits.all_points();
+        if matches!(d, Direction::South | Direction::East) {
+            points.reverse();
+        }
+        for p in points {
+            table[p] = match limits.step(p, d) {
+                None => None,
+                Some(next) if grid[next] == '#' => Some(p),
+                Some(next) => table[next],
+            };
+        }
+        table
     }
-    fn turn_guard(&mut self) {
-        self.guard_location.direction = self.guard_location.direction.right_turn()
+
+    fn is_obstructed(&self, p: Point) -> bool {
+        self.grid[p] == '#'
     }
-    fn move_guard(&mut self) {
-        let next_point = self.next_point().unwrap();
-        self.positions.entry(next_point).and_modify(|x| match x {
-            Space::Obstruction => panic!("guard can not move into obstructed position"),
-            Space::Unseen => {
-                *x = Space::Visited(HashMap::from([(self.guard_location.direction, 1)]))
+
+    /// Every state the guard passes through on the patrol, turns included, with an optional
+    /// extra obstruction. Stops when the guard leaves the map or starts repeating itself,
+    /// the flag is true for the latter.
+    fn patrol(&self, obstruction: Option<Point>) -> (Vec<Guard>, bool) {
+        let mut guard = self.guard;
+        let mut states = vec![guard];
+        let mut seen: HashSet<Guard> = HashSet::from([guard]);
+        while let Some(next) = self.limits.step(guard.point, guard.direction) {
+            if self.is_obstructed(next) || Some(next) == obstruction {
+                guard.direction = guard.direction.right_turn();
+            } else {
+                guard.point = next;
             }
-            Space::Visited(c) => {
-                if let Some(_) = c.insert(self.guard_location.direction, 1) {
-                    panic!("The guard has entered a loop")
-                }
+            if !seen.insert(guard) {
+                return (states, true);
             }
-        });
-        self.guard_location.point = next_point;
-        self.turns += 1;
+            states.push(guard);
+        }
+        (states, false)
     }
 
-    fn place_obstruction(&mut self, p: Point) {
-        self.positions.insert(p, Space::Obstruction);
+    /// Where the guard stops walking from `guard` with an extra obstruction placed at `o`.
+    fn jump(&self, guard: &Guard, o: Point) -> Option<Point> {
+        let p = guard.point;
+        let target = self.jumps[guard.direction as usize][p];
+        let distance_to_o = match guard.direction {
+            Direction::North if o.1 == p.1 && o.0 < p.0 => Some(p.0 - o.0),
+            Direction::South if o.1 == p.1 && o.0 > p.0 => Some(o.0 - p.0),
+            Direction::West if o.0 == p.0 && o.1 < p.1 => Some(p.1 - o.1),
+            Direction::East if o.0 == p.0 && o.1 > p.1 => Some(o.1 - p.1),
+            _ => None,
+        };
+        let distance_to_target = target.map(|t| t.0.abs_diff(p.0) + t.1.abs_diff(p.1));
+        match (distance_to_o, distance_to_target) {
+            (Some(to_o), Some(to_t)) if to_o > to_t => target,
+            (Some(_), _) => self.limits.step(o, guard.direction.opposite()),
+            (None, _) => target,
+        }
     }
 
-    fn next_move(&self) -> NextSpace {
-        match self.next_point() {
-            None => NextSpace::OutOfBounds,
-            Some(p) => match self.positions.get(&p).unwrap() {
-                Space::Obstruction => NextSpace::Obstructed,
-                Space::Unseen => NextSpace::Clear,
-                Space::Visited(c) => match c.get(&self.guard_location.direction) {
-                    None => NextSpace::Clear,
-                    Some(_) => NextSpace::Loopy,
-                },
-            },
+    /// Follows the guard from `guard` with an extra obstruction at `o` jumping between turns.
+    /// A loop is found once the guard turns at the same point facing the same way twice.
+    fn loops_with(&self, guard: Guard, o: Point) -> bool {
+        let mut guard = guard;
+        let mut turns: HashSet<Guard> = HashSet::new();
+        while let Some(point) = self.jump(&guard, o) {
+            guard = Guard {
+                point,
+                direction: guard.direction.right_turn(),
+            };
+            if !turns.insert(guard) {
+                return true;
+            }
         }
+        false
     }
-}
 
-pub fn part1(input: String) -> Result<String, error::Error> {
-    let mut lab = LabMap::from_input(&input);
+    /// Each point on the patrol is a candidate for a new obstruction, paired with the state
+    /// of the guard just before it first walks into that point.
+    /// Later visits don't matter as the guard would have already been blocked.
+    fn obstruction_candidates(&self) -> Vec<(Guard, Point)> {
+        let mut seen: HashSet<Point> = HashSet::from([self.guard.point]);
+        let mut candidates = Vec::new();
+        for guard in self.patrol(None).0 {
+            if let Some(next) = self.limits.step(guard.point, guard.direction) {
+                if !self.is_obstructed(next) && seen.insert(next) {
+                    candidates.push((guard, next));
+                }
+            }
+        }
+        candidates
+    }
 
-    while let NextSpace::Clear | NextSpace::Obstructed = lab.next_move() {
-        if let NextSpace::Obstructed = lab.next_move() {
-            lab.turn_guard()
-        } else {
-            lab.move_guard()
+    /// Draws the patrol in `states` the same way as the puzzle text, `|` and `-` for the
+    /// guard moving vertically and horizontally and `+` where it turns or crosses its own path.
+    /// The extra obstruction is drawn as `O` and the guard's latest position with its facing.
+    fn render(&self, states: &[Guard], obstruction: Option<Point>) -> String {
+        let mut canvas = self.grid.clone();
+        let mut mark = |p: Point, c: char| {
+            if p == self.guard.point {
+                return;
+            }
+            canvas[p] = match (canvas[p], c) {
+                ('.', c) => c,
+                ('|', '|') => '|',
+                ('-', '-') => '-',
+                _ => '+',
+            };
+        };
+        for (from, to) in states.iter().zip(states.iter().skip(1)) {
+            if from.point == to.point {
+                mark(from.point, '+');
+            } else {
+                let line = if from.direction.is_vertical() {
+                    '|'
+                } else {
+                    '-'
+                };
+                mark(from.point, line);
+                mark(to.point, line);
+            }
+        }
+        if let Some(o) = obstruction {
+            canvas[o] = 'O';
+        }
+        if let Some(guard) = states.last().filter(|g| g.point != self.guard.point) {
+            canvas[guard.point] = match guard.direction {
+                Direction::North => '^',
+                Direction::East => '>',
+                Direction::South => 'v',
+                Direction::West => '<',
+            };
         }
+        canvas
+            .as_rows()
+            .into_iter()
+            .map(|r| r.into_iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
     }
-    let seen_count = lab
-        .positions
-        .iter()
-        .flat_map(|(_, s)| match s {
-            Space::Visited(m) => Some(m),
-            _ => None,
-        })
-        .count();
+}
 
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let lab = LabMap::from_input(&input);
+    let seen_count = lab
+        .patrol(None)
+        .0
+        .into_iter()
+        .map(|g| g.point)
+        .collect::<HashSet<Point>>()
+        .len();
     Ok(seen_count.to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let mut lab = LabMap::from_input(&input);
-    let _starting_guard = lab.guard_location.point.clone();
-    while let NextSpace::Clear | NextSpace::Obstructed = lab.next_move() {
-        if let NextSpace::Obstructed = lab.next_move() {
-            lab.turn_guard()
-        } else {
-            lab.move_guard()
+    let lab = LabMap::from_input(&input);
+    let candidates = lab.obstruction_candidates();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(threads).max(1);
+    let counter: usize = thread::scope(|s| {
+        candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let lab = &lab;
+                s.spawn(move || chunk.iter().filter(|(g, o)| lab.loops_with(*g, *o)).count())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .sum()
+    });
+    Ok(counter.to_string())
+}
+
+/// Draws the guard's patrol, optionally with an extra obstruction given as `row,col`.
+///
+/// --step          print the patrol one move at a time, press enter to advance or q to stop
+/// --frames <dir>  write every move of the patrol to `<dir>/frame_{n}.txt`
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let lab = LabMap::from_input(&input);
+    let mut obstruction: Option<Point> = None;
+    let mut step = false;
+    let mut frames_dir: Option<&String> = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--step" => step = true,
+            "--frames" => {
+                frames_dir = Some(args.next().ok_or(error::Error::InvalidArgument(
+                    "--frames requires a directory".to_string(),
+                ))?)
+            }
+            point => {
+                let parsed = point
+                    .split_once(',')
+                    .and_then(|(r, c)| Some((r.parse().ok()?, c.parse().ok()?)))
+                    .filter(|(r, c)| lab.limits.in_limits(*r, *c));
+                match parsed {
+                    Some(p) if !lab.is_obstructed(p) && p != lab.guard.point => {
+                        obstruction = Some(p)
+                    }
+                    _ => return Err(error::Error::InvalidArgument(point.to_string())),
+                }
+            }
         }
     }
 
-    let mut possible_new_obstructions: Vec<Point> = Vec::new();
-    for (placement_point, first_pass_result) in &lab.positions {
-        if let Space::Visited(_place_dirs) = first_pass_result {
-            possible_new_obstructions.push(*placement_point);
+    let (states, looped) = lab.patrol(obstruction);
+    if let Some(dir) = frames_dir {
+        fs::create_dir_all(dir).map_err(error::Error::FileWriteFailed)?;
+        for i in 0..states.len() {
+            fs::write(
+                format!("{dir}/frame_{i:05}.txt"),
+                lab.render(&states[..=i], obstruction),
+            )
+            .map_err(error::Error::FileWriteFailed)?;
         }
     }
-    dbg!(&possible_new_obstructions);
-
-    let mut counter = 0;
-    for p in possible_new_obstructions {
-        lab = LabMap::from_input(&input);
-        lab.place_obstruction(p);
-        while let NextSpace::Clear | NextSpace::Obstructed = lab.next_move() {
-            if let NextSpace::Obstructed = lab.next_move() {
-                lab.turn_guard()
-            } else {
-                lab.move_guard()
+    if step {
+        let mut lines = io::stdin().lock().lines();
+        for i in 0..states.len() {
+            println!("{}\nmove {i}", lab.render(&states[..=i], obstruction));
+            match lines.next() {
+                Some(Ok(l)) if l.trim() == "q" => break,
+                Some(Ok(_)) => (),
+                _ => break,
             }
         }
-        // dbg!(lab.next_move());
-        if let NextSpace::Loopy = lab.next_move() {
-            counter += 1;
-        }
     }
-    Ok(counter.to_string())
+    let outcome = if looped {
+        "the guard is stuck in a loop"
+    } else {
+        "the guard leaves the lab"
+    };
+    Ok(format!(
+        "{}\n{outcome} after {} moves",
+        lab.render(&states, obstruction),
+        states.len() - 1
+    ))
 }
 
 #[cfg(test)]
@@ -260,4 +322,38 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "6")
     }
+    #[test]
+    fn test_render_loop() {
+        let lab = LabMap::from_input(INPUT);
+        let (states, looped) = lab.patrol(Some((6, 3)));
+        assert!(looped);
+        let expected = "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...";
+        assert_eq!(lab.render(&states, Some((6, 3))), expected)
+    }
+    #[test]
+    fn test_render_patrol() {
+        let lab = LabMap::from_input(INPUT);
+        let (states, looped) = lab.patrol(None);
+        assert!(!looped);
+        let expected = "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+-^-+-+.
+.+----++#.
+#+----+|..
+......#v..";
+        assert_eq!(lab.render(&states, None), expected)
+    }
 }
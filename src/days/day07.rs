@@ -1,3 +1,4 @@
+use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
     character::complete::{digit1, space1},
@@ -6,68 +7,279 @@ use nom::{
     sequence::separated_pair,
     IResult,
 };
-use std::{fmt::Debug, hash::Hash};
+use std::fmt::Debug;
 
 use crate::error;
 
 struct Calibration(u64, Vec<u64>);
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum Opperation {
-    Add,
-    Multiply,
-    Concatenation,
+/// Something that can be placed between two numbers of a calibration equation.
+/// The reverse search needs to be able to undo an operator as well as apply it.
+trait Operator: Debug + Sync {
+    /// `left op right`, None if that isn't defined or doesn't fit in a u64
+    fn apply(&self, left: u64, right: u64) -> Option<u64>;
+    /// every value of `left` for which `left op right` gives `result`, operators that aren't
+    /// invertible may give up on `right` values with too many to list
+    fn undo(&self, result: u64, right: u64) -> Vec<u64>;
+    fn symbol(&self) -> String;
+    /// higher precedences are evaluated first, equal precedences are evaluated left to right
+    fn precedence(&self) -> u8 {
+        0
+    }
+    /// whether `undo` gives at most one value, the reverse search branches on every value so
+    /// it's only used when all operators are invertible
+    fn is_invertible(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+struct Add;
+#[derive(Debug)]
+struct Multiply;
+#[derive(Debug)]
+struct Subtract;
+/// integer division rounding down
+#[derive(Debug)]
+struct Divide;
+/// the largest divisor `Divide::undo` lists the possible left hand sides for, there is one
+/// for every possible remainder
+const MAX_UNDO_DIVISOR: u64 = 1 << 16;
+/// appends the digits of the right hand side in the given base
+#[derive(Debug)]
+struct Concatenation {
+    base: u64,
+}
+/// an operator evaluated ahead of those with a lower precedence
+#[derive(Debug)]
+struct WithPrecedence<O: Operator> {
+    operator: O,
+    precedence: u8,
 }
 
-struct OperationNode {
-    value: u64,
+impl Operator for Add {
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_add(right)
+    }
+    fn undo(&self, result: u64, right: u64) -> Vec<u64> {
+        result.checked_sub(right).into_iter().collect()
+    }
+    fn symbol(&self) -> String {
+        "+".to_string()
+    }
+}
+
+impl Operator for Multiply {
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_mul(right)
+    }
+    /// calibration numbers are all positive so a zero right hand side is never undone
+    fn undo(&self, result: u64, right: u64) -> Vec<u64> {
+        match right {
+            0 => vec![],
+            r if result.is_multiple_of(r) => vec![result / r],
+            _ => vec![],
+        }
+    }
+    fn symbol(&self) -> String {
+        "*".to_string()
+    }
 }
 
-impl OperationNode {
-    fn new(value: u64) -> OperationNode {
-        OperationNode { value }
+impl Operator for Subtract {
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_sub(right)
     }
+    fn undo(&self, result: u64, right: u64) -> Vec<u64> {
+        result.checked_add(right).into_iter().collect()
+    }
+    fn symbol(&self) -> String {
+        "-".to_string()
+    }
+}
 
-    fn result(&self, i: &u64, o: &Opperation) -> OperationNode {
-        let value = match o {
-            Opperation::Add => self.value + i,
-            Opperation::Multiply => self.value * i,
-            Opperation::Concatenation => {
-                let mut s = self.value.to_string();
-                s.push_str(i.to_string().as_str());
-                s.parse().unwrap()
+impl Operator for Divide {
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_div(right)
+    }
+    fn undo(&self, result: u64, right: u64) -> Vec<u64> {
+        match result.checked_mul(right) {
+            Some(low) if right > 0 && right <= MAX_UNDO_DIVISOR => {
+                (0..right).map_while(|r| low.checked_add(r)).collect()
             }
-        };
-        OperationNode { value }
+            _ => vec![],
+        }
+    }
+    fn symbol(&self) -> String {
+        "/".to_string()
+    }
+    /// every left hand side up to `right - 1` past `result * right` rounds down to `result`
+    fn is_invertible(&self) -> bool {
+        false
     }
 }
 
+impl Concatenation {
+    /// `shift` would never pass a value in base 0 or 1
+    const fn new(base: u64) -> Concatenation {
+        assert!(base >= 2, "concatenation needs a base of at least 2");
+        Concatenation { base }
+    }
+
+    /// the power of the base with as many zeros as `value` has digits
+    fn shift(&self, value: u64) -> Option<u64> {
+        let mut shift = self.base;
+        while shift <= value {
+            shift = shift.checked_mul(self.base)?;
+        }
+        Some(shift)
+    }
+}
+
+impl Operator for Concatenation {
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_mul(self.shift(right)?)?.checked_add(right)
+    }
+    fn undo(&self, result: u64, right: u64) -> Vec<u64> {
+        match self.shift(right) {
+            Some(shift) if result % shift == right => vec![result / shift],
+            _ => vec![],
+        }
+    }
+    fn symbol(&self) -> String {
+        match self.base {
+            10 => "||".to_string(),
+            b => format!("||{b}"),
+        }
+    }
+}
+
+impl<O: Operator> Operator for WithPrecedence<O> {
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        self.operator.apply(left, right)
+    }
+    fn undo(&self, result: u64, right: u64) -> Vec<u64> {
+        self.operator.undo(result, right)
+    }
+    fn symbol(&self) -> String {
+        self.operator.symbol()
+    }
+    fn precedence(&self) -> u8 {
+        self.precedence
+    }
+    fn is_invertible(&self) -> bool {
+        self.operator.is_invertible()
+    }
+}
+
+type Operators<'a> = [&'a dyn Operator];
+
+const PART1_OPERATORS: [&dyn Operator; 2] = [&Add, &Multiply];
+const PART2_OPERATORS: [&dyn Operator; 3] = [&Add, &Multiply, &Concatenation::new(10)];
+const ARITHMETIC_OPERATORS: [&dyn Operator; 4] = [&Add, &Subtract, &Multiply, &Divide];
+/// multiplication and division are evaluated before addition and subtraction
+const PRECEDENCE_OPERATORS: [&dyn Operator; 4] = [
+    &Add,
+    &Subtract,
+    &WithPrecedence {
+        operator: Multiply,
+        precedence: 1,
+    },
+    &WithPrecedence {
+        operator: Divide,
+        precedence: 1,
+    },
+];
+
 impl Calibration {
     pub fn try_build(pair: (u64, Vec<u64>)) -> Result<Calibration, &'static str> {
         Ok(Calibration(pair.0, pair.1))
     }
 
-    fn recursive_possible_solution(
+    /// Works backwards from `target` peeling the last number off with each operator that
+    /// could have produced it, so most branches die straight away.
+    /// Only valid when every operator is evaluated left to right, and only quick when every
+    /// operator is invertible.
+    fn reverse_search<'a>(
         &self,
-        node: OperationNode,
-        from_index: usize,
-        op: &[Opperation],
-    ) -> Option<u64> {
-        if (from_index >= self.1.len()) & (node.value == self.0) {
-            Some(node.value)
-        } else if (from_index >= self.1.len()) | (node.value > self.0) {
-            None
+        target: u64,
+        to_index: usize,
+        operators: &Operators<'a>,
+    ) -> Option<Vec<&'a dyn Operator>> {
+        if to_index == 0 {
+            return (target == self.1[0]).then(Vec::new);
+        }
+        operators.iter().find_map(|o| {
+            o.undo(target, self.1[to_index])
+                .into_iter()
+                .find_map(|previous| self.reverse_search(previous, to_index - 1, operators))
+                .map(|mut solution| {
+                    solution.push(*o);
+                    solution
+                })
+        })
+    }
+
+    /// Tries every arrangement of operators, needed once precedence stops the equation being
+    /// read left to right or an operator can't be undone to a single value.
+    fn forward_search<'a>(
+        &self,
+        chosen: &mut Vec<&'a dyn Operator>,
+        operators: &Operators<'a>,
+    ) -> Option<Vec<&'a dyn Operator>> {
+        if chosen.len() == self.1.len() - 1 {
+            return (self.evaluate(chosen) == Some(self.0)).then(|| chosen.clone());
+        }
+        operators.iter().find_map(|o| {
+            chosen.push(*o);
+            let solution = self.forward_search(chosen, operators);
+            chosen.pop();
+            solution
+        })
+    }
+
+    /// the operators to place between the numbers to make the target
+    pub fn solution<'a>(&self, operators: &Operators<'a>) -> Option<Vec<&'a dyn Operator>> {
+        if operators.iter().map(|o| o.precedence()).all_equal()
+            && operators.iter().all(|o| o.is_invertible())
+        {
+            self.reverse_search(self.0, self.1.len() - 1, operators)
         } else {
-            op.iter()
-                .map(|o| node.result(&self.1[from_index], o))
-                .flat_map(|child| self.recursive_possible_solution(child, from_index + 1, op))
-                .next()
+            self.forward_search(&mut Vec::new(), operators)
         }
     }
 
-    pub fn possible_solution(&self, for_opperators: &[Opperation]) -> Option<u64> {
-        let parent = OperationNode::new(*&self.1[0]);
-        self.recursive_possible_solution(parent, 1, for_opperators)
+    pub fn equation(&self, solution: &Operators) -> String {
+        let mut equation = format!("{} = {}", self.0, self.1[0]);
+        for (o, n) in solution.iter().zip(self.1[1..].iter()) {
+            equation.push_str(&format!(" {} {n}", o.symbol()));
+        }
+        equation
+    }
+
+    /// Evaluates the numbers with `solution` between them respecting operator precedence,
+    /// None if any step is undefined.
+    pub fn evaluate(&self, solution: &Operators) -> Option<u64> {
+        let mut values = vec![self.1[0]];
+        let mut pending: Vec<&dyn Operator> = Vec::new();
+        fn reduce(values: &mut Vec<u64>, o: &dyn Operator) -> Option<()> {
+            let right = values.pop()?;
+            let left = values.pop()?;
+            values.push(o.apply(left, right)?);
+            Some(())
+        }
+        for (o, n) in solution.iter().zip(self.1[1..].iter()) {
+            while let Some(top) = pending.last().filter(|p| p.precedence() >= o.precedence()) {
+                reduce(&mut values, *top)?;
+                pending.pop();
+            }
+            pending.push(*o);
+            values.push(*n);
+        }
+        while let Some(top) = pending.pop() {
+            reduce(&mut values, top)?;
+        }
+        values.pop()
     }
 }
 
@@ -82,36 +294,58 @@ fn numbers(s: &str) -> IResult<&str, Vec<u64>> {
     separated_list1(space1, map_res(digit1, str::parse::<u64>))(s)
 }
 
-pub fn part1(input: String) -> Result<String, error::Error> {
-    let operations = [Opperation::Add, Opperation::Multiply];
-    let result: u64 = input
+fn total_calibration(input: &str, operators: &Operators) -> u64 {
+    input
         .lines()
         .map(calibrations)
         .map(|r| r.unwrap().1)
-        .filter_map(|c| c.possible_solution(&operations))
-        .sum();
-    Ok(result.to_string())
+        .filter(|c| c.solution(operators).is_some())
+        .map(|c| c.0)
+        .sum()
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    Ok(total_calibration(&input, &PART1_OPERATORS).to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let operations = [
-        Opperation::Add,
-        Opperation::Multiply,
-        Opperation::Concatenation,
-    ];
-    let result: Vec<u64> = input
+    Ok(total_calibration(&input, &PART2_OPERATORS).to_string())
+}
+
+/// Prints the equation that satisfies each calibration using the part 2 operators or the set
+/// named by the argument.
+///
+/// 1           `+ *`
+/// 2           `+ * ||`
+/// arithmetic  `+ - * /` evaluated left to right
+/// precedence  `+ - * /` with `*` and `/` evaluated first
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let operators: &Operators = match args.first().map(String::as_str) {
+        Some("1") => &PART1_OPERATORS,
+        Some("2") | None => &PART2_OPERATORS,
+        Some("arithmetic") => &ARITHMETIC_OPERATORS,
+        Some("precedence") => &PRECEDENCE_OPERATORS,
+        Some(x) => return Err(error::Error::InvalidArgument(x.to_string())),
+    };
+    Ok(input
         .lines()
         .map(calibrations)
         .map(|r| r.unwrap().1)
-        .flat_map(|c| c.possible_solution(&operations))
-        .collect();
-    dbg!(&result);
-    Ok(result.into_iter().sum::<u64>().to_string())
+        .map(|c| match c.solution(operators) {
+            Some(s) => {
+                debug_assert_eq!(c.evaluate(&s), Some(c.0));
+                c.equation(&s)
+            }
+            None => format!("{}: no solution", c.0),
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     const INPUT: &str = "190: 10 19
 3267: 81 40 27
 83: 17 5
@@ -135,4 +369,93 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "11387")
     }
+    #[test]
+    fn test_equation() {
+        let c = calibrations("7290: 6 8 6 15").unwrap().1;
+        let solution = c.solution(&PART2_OPERATORS).unwrap();
+        assert_eq!(c.equation(&solution), "7290 = 6 * 8 || 6 * 15");
+        assert_eq!(c.evaluate(&solution), Some(7290));
+        assert!(c.solution(&PART1_OPERATORS).is_none());
+    }
+    #[test]
+    fn test_concatenation() {
+        let decimal = Concatenation::new(10);
+        assert_eq!(decimal.undo(156, 6), vec![15]);
+        assert_eq!(decimal.undo(156, 56), vec![1]);
+        assert_eq!(decimal.undo(156, 7), vec![]);
+        assert_eq!(decimal.apply(15, 10), Some(1510));
+        let binary = Concatenation::new(2);
+        assert_eq!(binary.apply(0b101, 0b11), Some(0b10111));
+        assert_eq!(binary.symbol(), "||2");
+    }
+    #[test]
+    #[should_panic]
+    fn test_unary_concatenation() {
+        Concatenation::new(1);
+    }
+    #[test]
+    fn test_divide_undo() {
+        assert_eq!(Divide.undo(7, 3), vec![21, 22, 23]);
+        assert_eq!(Divide.undo(7, 0), vec![]);
+        assert_eq!(Divide.undo(1, u64::MAX), vec![]);
+    }
+    #[test]
+    fn test_precedence() {
+        let c = calibrations("14: 2 3 4").unwrap().1;
+        let solution = c.solution(&PRECEDENCE_OPERATORS).unwrap();
+        assert_eq!(c.equation(&solution), "14 = 2 + 3 * 4");
+        assert!(c.solution(&PART1_OPERATORS).is_none());
+    }
+    #[test]
+    fn test_non_invertible() {
+        let c = calibrations("7: 512 613 714 815 916 17 18").unwrap().1;
+        let solution = c.solution(&ARITHMETIC_OPERATORS).unwrap();
+        assert_eq!(c.evaluate(&solution), Some(7));
+        assert!(!Divide.is_invertible());
+        assert!(!PRECEDENCE_OPERATORS.iter().all(|o| o.is_invertible()));
+    }
+
+    const ALL_OPERATORS: [&dyn Operator; 6] = [
+        &Add,
+        &Multiply,
+        &Subtract,
+        &Divide,
+        &Concatenation::new(10),
+        &Concatenation::new(3),
+    ];
+
+    proptest! {
+        #[test]
+        fn undo_reverses_apply(left in 0u64..100_000, right in 1u64..1000, i in 0..ALL_OPERATORS.len()) {
+            let o = ALL_OPERATORS[i];
+            if let Some(result) = o.apply(left, right) {
+                prop_assert!(o.undo(result, right).contains(&left), "{left} {} {right}", o.symbol());
+            }
+        }
+
+        #[test]
+        fn reverse_search_agrees_with_forward(
+            numbers in proptest::collection::vec(1u64..20, 2..6),
+            choices in proptest::collection::vec(0..ALL_OPERATORS.len(), 5),
+        ) {
+            let chosen: Vec<&dyn Operator> = choices[..numbers.len() - 1]
+                .iter()
+                .map(|i| ALL_OPERATORS[*i])
+                .collect();
+            let c = Calibration(0, numbers.clone());
+            if let Some(target) = c.evaluate(&chosen) {
+                let c = Calibration(target, numbers);
+                let solution = c.solution(&ALL_OPERATORS);
+                prop_assert!(solution.is_some());
+                prop_assert_eq!(c.evaluate(&solution.unwrap()), Some(target));
+                if chosen.iter().all(|o| o.is_invertible()) {
+                    let invertible: Vec<&dyn Operator> =
+                        ALL_OPERATORS.into_iter().filter(|o| o.is_invertible()).collect();
+                    let reversed = c.reverse_search(target, c.1.len() - 1, &invertible);
+                    prop_assert!(reversed.is_some());
+                    prop_assert_eq!(c.evaluate(&reversed.unwrap()), Some(target));
+                }
+            }
+        }
+    }
 }
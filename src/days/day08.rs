@@ -1,11 +1,10 @@
-use std::collections::{HashMap, HashSet};
+use std::{collections::HashMap, ops::RangeInclusive};
 
-use crate::error;
+use array2d::Array2D;
+use itertools::Itertools;
+use num::integer::gcd;
 
-enum Part {
-    One,
-    Two,
-}
+use crate::{error, utils::grid_from_input};
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
 struct Point(i32, i32);
@@ -17,131 +16,148 @@ impl Point {
     fn diff(&self, other: &Point) -> Point {
         Point(self.0 - other.0, self.1 - other.1)
     }
-    fn inlimit(&self, num_cols: usize, num_rows: usize) -> bool {
-        (self.0 >= 0) & (self.0 < num_rows as i32) & (self.1 >= 0) & (self.1 < num_cols as i32)
+    fn scale(&self, k: i32) -> Point {
+        Point(self.0 * k, self.1 * k)
+    }
+    /// the smallest step in the same direction that lands on whole grid points, and how many
+    /// of those steps make up the original
+    fn reduced(&self) -> (Point, i32) {
+        let g = gcd(self.0, self.1).max(1);
+        (Point(self.0 / g, self.1 / g), g)
     }
-    fn resonant_points(&self, other: &Point, num_cols: usize, num_rows: usize) -> Vec<Point> {
-        let (x, y) = (self.add(&self.diff(other)), other.add(&other.diff(self)));
-        let mut v = Vec::new();
-        if x.inlimit(num_cols, num_rows) {
-            v.push(x)
+}
+
+/// What antinodes are made by a pair of antennas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// the distance between the antennas
+    Spacing,
+    /// the gap between neighbouring grid points on the line through the antennas, so points
+    /// between the antennas are on it too
+    Lattice,
+}
+
+/// Antinodes are found some number of steps out from each antenna of a pair, away from the
+/// other. Harmonic 0 is the antennas themselves along with anything between them, an
+/// unbounded range runs on to the edge of the map.
+#[derive(Debug, Clone)]
+struct Harmonics {
+    range: RangeInclusive<usize>,
+    step: Step,
+}
+
+/// an antinode one antenna spacing beyond each antenna, twice as far from the other one
+const PART1_HARMONICS: Harmonics = Harmonics {
+    range: 1..=1,
+    step: Step::Spacing,
+};
+/// every grid point in line with the antennas
+const PART2_HARMONICS: Harmonics = Harmonics {
+    range: 0..=usize::MAX,
+    step: Step::Lattice,
+};
+
+struct AntennaMap {
+    grid: Array2D<char>,
+    antennas: HashMap<char, Vec<Point>>,
+}
+
+impl AntennaMap {
+    fn from_input(input: &str) -> AntennaMap {
+        let grid = grid_from_input(input);
+        let mut antennas: HashMap<char, Vec<Point>> = HashMap::new();
+        for ((i, j), c) in grid.enumerate_row_major().filter(|(_, c)| **c != '.') {
+            antennas
+                .entry(*c)
+                .or_default()
+                .push(Point(i as i32, j as i32));
         }
-        if y.inlimit(num_cols, num_rows) {
-            v.push(y)
+        AntennaMap { grid, antennas }
+    }
+
+    fn index(&self, p: &Point) -> Option<(usize, usize)> {
+        let (i, j) = (usize::try_from(p.0).ok()?, usize::try_from(p.1).ok()?);
+        (i < self.grid.num_rows() && j < self.grid.num_columns()).then_some((i, j))
+    }
+
+    /// Marks the antinodes of the pair `a`, `b` on the map.
+    fn mark_pair(&self, a: Point, b: Point, harmonics: &Harmonics, marks: &mut Array2D<bool>) {
+        let spacing = b.diff(&a);
+        let (step, between) = match harmonics.step {
+            Step::Spacing => (spacing, 1),
+            Step::Lattice => spacing.reduced(),
+        };
+        if harmonics.range.contains(&0) {
+            for k in 0..=between {
+                marks[self.index(&a.add(&step.scale(k))).unwrap()] = true;
+            }
         }
-        v
-    }
-    fn resonant_lines<'a>(&self, other: &Point, num_rows: usize, num_cols: usize) -> Vec<Point> {
-        let up_diff = self.diff(other);
-        let mut up_resonant_point = self.add(&up_diff);
-        let dn_diff = other.diff(&self);
-        let mut dn_resonant_point = other.add(&dn_diff);
-        let mut result_set: Vec<Point> = vec![self.clone(), other.clone()];
-        while up_resonant_point.inlimit(num_cols, num_rows) {
-            result_set.push(up_resonant_point.clone());
-            up_resonant_point = up_resonant_point.add(&up_diff);
+        // walk out from each antenna until leaving the map or the range
+        for (from, step) in [(a, step.scale(-1)), (b, step)] {
+            for k in harmonics.range.clone().filter(|k| *k > 0) {
+                match self.index(&from.add(&step.scale(k as i32))) {
+                    Some(p) => marks[p] = true,
+                    None => break,
+                }
+            }
         }
-        while dn_resonant_point.inlimit(num_cols, num_rows) {
-            result_set.push(dn_resonant_point.clone());
-            dn_resonant_point = dn_resonant_point.add(&dn_diff);
+    }
+
+    fn antinodes(&self, harmonics: &Harmonics) -> Array2D<bool> {
+        let mut marks = Array2D::filled_with(false, self.grid.num_rows(), self.grid.num_columns());
+        for points in self.antennas.values() {
+            for (a, b) in points.iter().tuple_combinations() {
+                self.mark_pair(*a, *b, harmonics, &mut marks);
+            }
         }
-        result_set
+        marks
     }
-}
 
-fn recursively_find_resonances(
-    completed: &mut Vec<Point>,
-    remaining: &[Point],
-    acc: HashSet<Point>,
-    num_rows: usize,
-    num_cols: usize,
-    part: Part,
-) -> HashSet<Point> {
-    if remaining.is_empty() {
-        acc
-    } else {
-        let p = remaining[0];
-        let resonances: HashSet<Point> = completed
-            .iter()
-            .flat_map(|seen| match part {
-                Part::One => seen.resonant_points(&p, num_cols, num_rows),
-                Part::Two => seen.resonant_lines(&p, num_cols, num_rows),
+    /// The map as drawn in the puzzle, antinodes are `#` unless there is an antenna there.
+    fn render(&self, antinodes: &Array2D<bool>) -> String {
+        self.grid
+            .rows_iter()
+            .zip(antinodes.rows_iter())
+            .map(|(row, marks)| {
+                row.zip(marks)
+                    .map(|(c, marked)| match (c, marked) {
+                        ('.', true) => '#',
+                        (c, _) => *c,
+                    })
+                    .collect::<String>()
             })
-            .chain(acc.into_iter())
-            .collect();
-        completed.push(p);
-        recursively_find_resonances(
-            completed,
-            &remaining[1..],
-            resonances,
-            num_rows,
-            num_cols,
-            part,
-        )
+            .join("\n")
     }
 }
 
-fn resonances(p: &Vec<Point>, num_rows: usize, num_cols: usize, part: Part) -> HashSet<Point> {
-    let mut first_is_completed = vec![p[0]];
-    let remaining = &p[1..];
-    recursively_find_resonances(
-        &mut first_is_completed,
-        remaining,
-        HashSet::new(),
-        num_rows,
-        num_cols,
-        part,
-    )
-}
-
-fn parse_input(input: &str) -> HashMap<char, Vec<Point>> {
-    let mut antenna: HashMap<char, Vec<Point>> = HashMap::new();
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(i, x)| {
-            x.chars().enumerate().flat_map(move |(j, c)| match c {
-                '.' => None,
-                x => Some((x, Point(i as i32, j as i32))),
-            })
-        })
-        .for_each(|(c, p)| {
-            antenna
-                .entry(c)
-                .and_modify(|v| {
-                    v.push(p);
-                })
-                .or_insert(vec![p]);
-        });
-    antenna
+fn count(marks: &Array2D<bool>) -> usize {
+    marks.elements_row_major_iter().filter(|m| **m).count()
 }
 
 pub fn part1(input: String) -> Result<String, error::Error> {
-    let num_rows = input.lines().count();
-    let num_cols = input.lines().next().unwrap().len();
-    let antenna = parse_input(&input);
-
-    let result = antenna
-        .values()
-        .map(|x| resonances(x, num_rows, num_cols, Part::One))
-        .reduce(|x, y| x.union(&y).copied().collect())
-        .unwrap();
-
-    Ok(result.len().to_string())
+    let map = AntennaMap::from_input(&input);
+    Ok(count(&map.antinodes(&PART1_HARMONICS)).to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let num_rows = input.lines().count();
-    let num_cols = input.lines().next().unwrap().len();
-    let antenna = parse_input(&input);
-
-    let result = antenna
-        .values()
-        .map(|x| resonances(x, num_rows, num_cols, Part::Two))
-        .reduce(|x, y| x.union(&y).copied().collect())
-        .unwrap();
+    let map = AntennaMap::from_input(&input);
+    Ok(count(&map.antinodes(&PART2_HARMONICS)).to_string())
+}
 
-    Ok(result.iter().count().to_string())
+/// Draws the antinodes on the map, for part 2 unless given `1`.
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let harmonics = match args.first().map(String::as_str) {
+        Some("1") => PART1_HARMONICS,
+        Some("2") | None => PART2_HARMONICS,
+        Some(x) => return Err(error::Error::InvalidArgument(x.to_string())),
+    };
+    let map = AntennaMap::from_input(&input);
+    let antinodes = map.antinodes(&harmonics);
+    Ok(format!(
+        "{}\n{} antinodes",
+        map.render(&antinodes),
+        count(&antinodes)
+    ))
 }
 
 #[cfg(test)]
@@ -166,11 +182,35 @@ mod tests {
 
     #[test]
     fn test_resonant_point() {
-        let p1 = Point(1, 2);
-        let p2 = Point(2, 4);
-        let expected = p1.resonant_points(&p2, 10, 10);
+        let map = AntennaMap::from_input(
+            "..........
+.a........
+..a.......
+..........",
+        );
+        let expected = "#.........
+.a........
+..a.......
+...#......";
+        assert_eq!(map.render(&map.antinodes(&PART1_HARMONICS)), expected)
+    }
 
-        assert!(vec![Point(0, 0), Point(3, 6)] == expected, "{:?}", expected)
+    #[test]
+    fn test_lattice_points_between() {
+        // the antennas are 2 rows and 4 columns apart so the line through them has a grid
+        // point halfway between and every 2 columns
+        let map = AntennaMap::from_input(
+            ".....
+a....
+.....
+....a",
+        );
+        assert_eq!(Point(2, 4).reduced(), (Point(1, 2), 2));
+        let expected = ".....
+a....
+..#..
+....a";
+        assert_eq!(map.render(&map.antinodes(&PART2_HARMONICS)), expected)
     }
 
     #[test]
@@ -187,4 +227,26 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "34")
     }
+    #[test]
+    fn test_render_part1() {
+        let map = AntennaMap::from_input(&sanitise_test_input());
+        assert_eq!(map.render(&map.antinodes(&PART1_HARMONICS)), INPUT)
+    }
+    #[test]
+    fn test_render_part2() {
+        let map = AntennaMap::from_input(&sanitise_test_input());
+        let expected = "##....#....#
+.#.#....0...
+..#.#0....#.
+..##...0....
+....0....#..
+.#...#A....#
+...#..#.....
+#....#.#....
+..#.....A...
+....#....A..
+.#........#.
+...#......##";
+        assert_eq!(map.render(&map.antinodes(&PART2_HARMONICS)), expected)
+    }
 }
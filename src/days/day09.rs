@@ -1,354 +1,182 @@
-use std::{collections::VecDeque, env};
-
-use itertools::Itertools;
+use std::{cmp::Reverse, collections::BinaryHeap};
 
 use crate::error;
 
-enum Memory {
-    File { id: usize, _position: usize },
-    Free { _position: usize },
-}
-impl Memory {
-    fn parse_input(input: &str) -> VecDeque<Memory> {
-        let (_, mem_chunks) = input.chars().enumerate().fold(
-            (0, VecDeque::<Memory>::new()),
-            |(position, mut acc), (i, c)| {
-                let size: usize = c.to_digit(10).unwrap() as usize;
-                if i % 2 == 0 {
-                    (0..size).for_each(|p| {
-                        acc.push_back(Memory::File {
-                            id: i / 2,
-                            _position: position + p,
-                        })
-                    });
-                    (position + size, acc)
-                } else {
-                    (0..size).for_each(|p| {
-                        acc.push_back(Memory::Free {
-                            _position: position + p,
-                        })
-                    });
-                    (position + size, acc)
-                }
-            },
-        );
-        mem_chunks
-    }
+/// the largest file that fits in a single digit of the disk map
+const MAX_SIZE: usize = 9;
 
-    fn _position(&self) -> usize {
-        match self {
-            Memory::File {
-                id: _,
-                _position: position,
-            } => *position,
-            Memory::Free {
-                _position: position,
-            } => *position,
-        }
-    }
-}
-#[derive(Debug, Clone, Copy)]
-enum ContiguousMemory {
-    File {
-        id: usize,
-        start: usize,
-        size: usize,
-    },
-    Defrag {
-        id: usize,
-        start: usize,
-        size: usize,
-        _original_start: usize,
-    },
-    Free {
-        start: usize,
-        size: usize,
-    },
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: usize,
+    size: usize,
 }
 
-impl ContiguousMemory {
-    fn parse_input(input: &str) -> VecDeque<ContiguousMemory> {
-        let (_, mem_chunks) = input.chars().enumerate().fold(
-            (0, VecDeque::<ContiguousMemory>::new()),
-            |(position, mut acc), (i, c)| {
-                let size: usize = c.to_digit(10).unwrap() as usize;
-                if i % 2 == 0 {
-                    acc.push_back(ContiguousMemory::File {
-                        id: i / 2,
-                        start: position,
-                        size,
-                    });
-                    (position + size, acc)
-                } else {
-                    if size > 0 {
-                        acc.push_back(ContiguousMemory::Free {
-                            start: position,
-                            size,
-                        });
-                    }
-                    (position + size, acc)
-                }
-            },
-        );
-        mem_chunks
-    }
-    fn start(&self) -> usize {
-        match self {
-            ContiguousMemory::File {
-                id: _,
-                start,
-                size: _,
-            } => *start,
-            ContiguousMemory::Free { start, size: _ } => *start,
-            ContiguousMemory::Defrag {
-                id: _,
-                start,
-                size: _,
-                _original_start: _,
-            } => *start,
-        }
-    }
+impl Span {
     fn end(&self) -> usize {
-        self.start() + self.size()
-    }
-    fn size(&self) -> usize {
-        match self {
-            ContiguousMemory::File {
-                id: _,
-                start: _,
-                size,
-            } => *size,
-            ContiguousMemory::Free { start: _, size } => *size,
-            ContiguousMemory::Defrag {
-                id: _,
-                start: _,
-                size,
-                _original_start: _,
-            } => *size,
-        }
+        self.start + self.size
     }
-    fn id(&self) -> usize {
-        match self {
-            ContiguousMemory::File {
-                id,
-                start: _,
-                size: _,
-            } => *id,
-            ContiguousMemory::Free { start: _, size: _ } => panic!("Free does not have an id"),
-            ContiguousMemory::Defrag {
-                id,
-                start: _,
-                size: _,
-                _original_start: _,
-            } => *id,
-        }
-    }
-}
 
-pub fn part1(input: String) -> Result<String, error::Error> {
-    let mut defrag: Vec<usize> = Vec::new();
-    let mut mem_chunks = Memory::parse_input(&input);
-    while let Some(x) = mem_chunks.pop_front() {
-        match x {
-            Memory::File { id, _position: _ } => defrag.push(id),
-            _ => {
-                while let Some(y) = mem_chunks.pop_back() {
-                    match y {
-                        Memory::File { id, _position: _ } => {
-                            defrag.push(id);
-                            break;
-                        }
-                        _ => (),
-                    }
-                }
-            }
-        }
+    /// sum of every block position in the span
+    fn position_sum(&self) -> usize {
+        self.size * self.start + self.size * self.size.saturating_sub(1) / 2
     }
-    let result: usize = defrag.iter().enumerate().map(|(i, f)| i * f).sum();
+}
 
-    Ok(result.to_string())
+#[derive(Debug, Clone)]
+struct DiskMap {
+    length: usize,
+    /// where the blocks of each file id are stored, a file only has more than one span once
+    /// it has been broken up by compacting blocks
+    files: Vec<Vec<Span>>,
+    /// gaps between the files as they were originally laid out, gaps either side of an empty
+    /// file are joined together
+    free: Vec<Span>,
 }
 
-fn prepend(mem_vec: &mut VecDeque<ContiguousMemory>, item: ContiguousMemory, dbg_str: &str) {
-    match mem_vec.pop_front() {
-        None => {
-            mem_vec.push_front(item);
-        }
-        Some(first) => {
-            if item.end() != first.start() {
-                panic!(
-                    "memory in {dbg_str} is not contiguous {:?} {:?}",
-                    first, item
-                )
+impl DiskMap {
+    fn from_input(input: &str) -> DiskMap {
+        let mut disk = DiskMap {
+            length: 0,
+            files: Vec::new(),
+            free: Vec::new(),
+        };
+        for (i, size) in input
+            .trim()
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .enumerate()
+        {
+            let span = Span {
+                start: disk.length,
+                size: size as usize,
+            };
+            match disk.free.last_mut() {
+                _ if i % 2 == 0 => disk.files.push(vec![span]),
+                _ if span.size == 0 => (),
+                Some(gap) if gap.end() == span.start => gap.size += span.size,
+                _ => disk.free.push(span),
             }
-            mem_vec.push_front(first);
-            mem_vec.push_front(item);
+            disk.length = span.end();
         }
+        disk
     }
-}
 
-fn append(mem_vec: &mut VecDeque<ContiguousMemory>, item: ContiguousMemory, dbg_str: &str) {
-    match mem_vec.pop_back() {
-        None => {
-            mem_vec.push_back(item);
-        }
-        Some(last) => {
-            if last.end() != item.start() {
-                panic!(
-                    "memory in {dbg_str} is not contiguous {:?} {:?}",
-                    last, item
-                )
+    /// Fills each gap from the left with blocks taken from the end of the rightmost file,
+    /// splitting files where they don't fit.
+    /// `on_move` is given the disk map after each run of blocks is moved.
+    fn compact_blocks(&mut self, mut on_move: impl FnMut(&DiskMap)) {
+        let Some(mut last) = self.files.len().checked_sub(1) else {
+            return;
+        };
+        for mut gap in self.free.clone() {
+            while gap.size > 0 {
+                while last > 0 && self.files[last][0].size == 0 {
+                    last -= 1;
+                }
+                let file = self.files[last][0];
+                if file.size == 0 || file.start < gap.start {
+                    return;
+                }
+                let moved = gap.size.min(file.size);
+                self.files[last][0].size -= moved;
+                self.files[last].push(Span {
+                    start: gap.start,
+                    size: moved,
+                });
+                gap.start += moved;
+                gap.size -= moved;
+                on_move(self);
             }
-            mem_vec.push_back(last);
-            mem_vec.push_back(item);
         }
     }
-}
-
-fn scan(
-    mem_chunks: &mut VecDeque<ContiguousMemory>,
-    tail: &mut VecDeque<ContiguousMemory>,
-    cache: &mut VecDeque<ContiguousMemory>,
-) -> VecDeque<ContiguousMemory> {
-    // defrag each result in mem_chunks
-    while let Some(memory_for_defrag) = mem_chunks.pop_back() {
-        match memory_for_defrag {
-            ContiguousMemory::File {
-                id,
-                start: move_file_start,
-                size: move_file_size,
-            } => {
-                let mut inserted = false;
-                while let Some(insertion_position) = mem_chunks.pop_front() {
-                    match insertion_position {
-                        ContiguousMemory::Free {
-                            start: free_mem_start,
-                            size: free_mem_size,
-                        } => {
-                            if free_mem_start > move_file_start {
-                                // file is not to the left and can't be inserted
-                                prepend(
-                                    tail,
-                                    ContiguousMemory::Defrag {
-                                        id,
-                                        start: move_file_start,
-                                        size: move_file_size,
-                                        _original_start: move_file_start,
-                                    },
-                                    "tail",
-                                );
-                                inserted = true;
-                                break;
-                            } else if free_mem_size < move_file_size {
-                                // file does not fit
-                                append(cache, insertion_position, "cache");
-                            } else if free_mem_size == move_file_size {
-                                // file fits exactly
-                                append(
-                                    cache,
-                                    ContiguousMemory::Defrag {
-                                        id,
-                                        start: free_mem_start,
-                                        size: move_file_size,
-                                        _original_start: move_file_start,
-                                    },
-                                    "cache",
-                                );
-                                prepend(
-                                    tail,
-                                    ContiguousMemory::Free {
-                                        start: move_file_start,
-                                        size: free_mem_size,
-                                    },
-                                    "tail",
-                                );
-                                inserted = true;
-                                break;
-                            } else {
-                                // file fits and there is some remaining free space
-                                let remaining_mem_size = free_mem_size - move_file_size;
-                                let remaining_mem_start = free_mem_start + move_file_size;
 
-                                append(
-                                    cache,
-                                    ContiguousMemory::Defrag {
-                                        id,
-                                        start: free_mem_start,
-                                        size: move_file_size,
-                                        _original_start: move_file_start,
-                                    },
-                                    "cache",
-                                );
-                                append(
-                                    cache,
-                                    ContiguousMemory::Free {
-                                        start: remaining_mem_start,
-                                        size: remaining_mem_size,
-                                    },
-                                    "cache",
-                                );
-                                prepend(
-                                    tail,
-                                    ContiguousMemory::Free {
-                                        start: move_file_start,
-                                        size: move_file_size,
-                                    },
-                                    "tail",
-                                );
-                                inserted = true;
-                                break;
-                            }
-                        }
-                        occupied => append(cache, occupied, "cache"),
-                    }
-                }
-                if !inserted {
-                    prepend(
-                        tail,
-                        ContiguousMemory::Defrag {
-                            id,
-                            start: move_file_start,
-                            size: move_file_size,
-                            _original_start: move_file_start,
+    /// Moves each file once, in order of decreasing id, into the leftmost gap it fits in.
+    /// Gaps are kept in a min-heap ordered by start for each size, gaps of 9 or more sharing
+    /// the last heap, so the leftmost gap of any size `>= file size` is found by peeking at no
+    /// more than 9 heaps.
+    /// `on_move` is given the disk map after each file is moved.
+    fn compact_files(&mut self, mut on_move: impl FnMut(&DiskMap)) {
+        let mut gaps: Vec<BinaryHeap<Reverse<(usize, usize)>>> =
+            vec![BinaryHeap::new(); MAX_SIZE + 1];
+        let add_gap = |gaps: &mut Vec<BinaryHeap<_>>, gap: Span| {
+            gaps[gap.size.min(MAX_SIZE)].push(Reverse((gap.start, gap.size)))
+        };
+        for gap in &self.free {
+            add_gap(&mut gaps, *gap);
+        }
+        for id in (0..self.files.len()).rev() {
+            let file = self.files[id][0];
+            if file.size == 0 {
+                continue;
+            }
+            let leftmost = (file.size..=MAX_SIZE)
+                .filter_map(|i| gaps[i].peek().map(|Reverse(gap)| (*gap, i)))
+                .filter(|((start, _), _)| *start < file.start)
+                .min();
+            if let Some(((start, size), i)) = leftmost {
+                gaps[i].pop();
+                // the space the file leaves behind is to the right of every file still to
+                // move so it never needs to go back into the heaps
+                if size > file.size {
+                    add_gap(
+                        &mut gaps,
+                        Span {
+                            start: start + file.size,
+                            size: size - file.size,
                         },
-                        "tail",
                     );
                 }
-                while let Some(value) = cache.pop_back() {
-                    prepend(mem_chunks, value, "mem_chunks");
-                }
-            }
-            x => {
-                // no need to move free space or previously defragged files, add to tail
-                prepend(tail, x, "tail");
+                self.files[id][0].start = start;
+                on_move(self);
             }
         }
     }
-    // if defrag is empty, iteration complete return tail, else rebuild mem_chunks from defrag, this should be possible with a swap
-    if mem_chunks.is_empty() & cache.is_empty() {
-        tail.clone()
-    } else {
-        panic!("defrag still contains values at end of loop")
+
+    fn checksum(&self) -> usize {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(id, spans)| id * spans.iter().map(Span::position_sum).sum::<usize>())
+            .sum()
+    }
+
+    /// Draws each block as the last digit of the file id stored in it, or `.` if free.
+    fn render(&self) -> String {
+        let mut blocks = vec!['.'; self.length];
+        for (id, spans) in self.files.iter().enumerate() {
+            let c = char::from_digit((id % 10) as u32, 10).unwrap();
+            for span in spans {
+                blocks[span.start..span.end()].fill(c);
+            }
+        }
+        blocks.into_iter().collect()
     }
 }
 
-pub fn part2(input: String) -> Result<String, error::Error> {
-    env::set_var("RUST_BACKTRACE", "1");
-    let mut defrag: VecDeque<ContiguousMemory> = VecDeque::new();
-    let mut tail: VecDeque<ContiguousMemory> = VecDeque::new();
-    let mut mem_chunks = ContiguousMemory::parse_input(&input);
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let mut disk = DiskMap::from_input(&input);
+    disk.compact_blocks(|_| ());
+    Ok(disk.checksum().to_string())
+}
 
-    let result_memory = scan(&mut mem_chunks, &mut tail, &mut defrag);
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let mut disk = DiskMap::from_input(&input);
+    disk.compact_files(|_| ());
+    Ok(disk.checksum().to_string())
+}
 
-    let checksum: usize = result_memory
-        .iter()
-        .map(|x| match x {
-            ContiguousMemory::Free { start: _, size: _ } => Vec::new(),
-            x => (x.start()..x.end()).map(|i| x.id() * i).collect_vec(),
-        })
-        .flat_map(|x| x)
-        .sum();
-    Ok(checksum.to_string())
+/// Prints the block map before compacting and after every move, pass `1` to compact blocks
+/// as in part 1 rather than whole files.
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let mut disk = DiskMap::from_input(&input);
+    let mut frames = vec![disk.render()];
+    match args.first().map(String::as_str) {
+        Some("1") => disk.compact_blocks(|d| frames.push(d.render())),
+        Some("2") | None => disk.compact_files(|d| frames.push(d.render())),
+        Some(x) => return Err(error::Error::InvalidArgument(x.to_string())),
+    }
+    frames.push(format!("checksum {}", disk.checksum()));
+    Ok(frames.join("\n"))
 }
 
 #[cfg(test)]
@@ -369,4 +197,36 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "2858")
     }
+    #[test]
+    fn test_render_blocks() {
+        let mut disk = DiskMap::from_input("12345");
+        let mut frames = vec![disk.render()];
+        disk.compact_blocks(|d| frames.push(d.render()));
+        assert_eq!(frames[0], "0..111....22222");
+        assert_eq!(frames.last().unwrap(), "022111222......");
+
+        let mut disk = DiskMap::from_input(INPUT);
+        disk.compact_blocks(|_| ());
+        assert_eq!(disk.render(), "0099811188827773336446555566..............");
+    }
+    #[test]
+    fn test_render_files() {
+        let mut disk = DiskMap::from_input(INPUT);
+        let mut frames = vec![disk.render()];
+        disk.compact_files(|d| frames.push(d.render()));
+        let expected = "00...111...2...333.44.5555.6666.777.888899
+0099.111...2...333.44.5555.6666.777.8888..
+0099.1117772...333.44.5555.6666.....8888..
+0099.111777244.333....5555.6666.....8888..
+00992111777.44.333....5555.6666.....8888..";
+        assert_eq!(frames.join("\n"), expected);
+    }
+    #[test]
+    fn test_empty_file_joins_gaps() {
+        // file 3 only fits in the gaps either side of the empty file 1 together
+        let mut disk = DiskMap::from_input("1201203");
+        assert_eq!(disk.free, vec![Span { start: 1, size: 3 }]);
+        disk.compact_files(|_| ());
+        assert_eq!(disk.render(), "033322...");
+    }
 }
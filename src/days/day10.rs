@@ -1,128 +1,149 @@
 use std::collections::HashSet;
 
-use crate::error;
+use array2d::Array2D;
+use itertools::Itertools;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-struct Topogaphy {
-    row_num: i32,
-    col_num: i32,
-    height: i32,
-}
+use crate::{
+    error,
+    utils::{grid_from_input, ArrayOrientation, ArrayUtils2D},
+};
 
-impl Topogaphy {
-    fn build(i: usize, j: usize, c: char) -> Topogaphy {
-        Topogaphy {
-            row_num: i as i32,
-            col_num: j as i32,
-            height: c.to_digit(10).unwrap() as i32,
-        }
-    }
-    fn ascending_neighbors(&self) -> Vec<Topogaphy> {
-        vec![
-            Topogaphy {
-                row_num: self.row_num + 1,
-                col_num: self.col_num,
-                height: self.height + 1,
-            },
-            Topogaphy {
-                row_num: self.row_num - 1,
-                col_num: self.col_num,
-                height: self.height + 1,
-            },
-            Topogaphy {
-                row_num: self.row_num,
-                col_num: self.col_num + 1,
-                height: self.height + 1,
-            },
-            Topogaphy {
-                row_num: self.row_num,
-                col_num: self.col_num - 1,
-                height: self.height + 1,
-            },
-        ]
-    }
+type Point = (usize, usize);
+
+struct Trailhead {
+    point: Point,
+    score: usize,
+    rating: usize,
 }
 
 struct LavaProductionFacility {
-    map: HashSet<Topogaphy>,
-    starting_positions: Vec<Topogaphy>,
+    heights: Array2D<u32>,
+    limits: ArrayUtils2D,
+    /// the 9s that can be reached by climbing from each point
+    peaks: Array2D<HashSet<Point>>,
+    /// the number of distinct climbs from each point to any 9
+    paths: Array2D<usize>,
 }
 
 impl LavaProductionFacility {
     fn from_input(input: &str) -> LavaProductionFacility {
-        let mut starting_positions: Vec<Topogaphy> = Vec::new();
-        // let successful_paths: HashSet<[Topogaphy; 10]> = HashSet::new();
-        let map: HashSet<Topogaphy> = input
-            .lines()
-            .enumerate()
-            .flat_map(|(i, ln)| {
-                ln.chars()
-                    .enumerate()
-                    .map(move |(j, c)| Topogaphy::build(i, j, c))
-            })
-            .inspect(|p| {
-                if p.height == 0 {
-                    starting_positions.push(*p);
+        let grid = grid_from_input(input);
+        let heights = Array2D::from_iter_row_major(
+            grid.elements_row_major_iter()
+                .map(|c| c.to_digit(10).unwrap_or(u32::MAX)),
+            grid.num_rows(),
+            grid.num_columns(),
+        )
+        .unwrap();
+        let limits = ArrayUtils2D::from_array(&heights);
+        let mut peaks =
+            Array2D::filled_with(HashSet::new(), heights.num_rows(), heights.num_columns());
+        let mut paths = Array2D::filled_with(0, heights.num_rows(), heights.num_columns());
+        // working down from the 9s means every point a step higher is already known
+        for height in (0..=9).rev() {
+            for p in limits
+                .all_points()
+                .into_iter()
+                .filter(|p| heights[*p] == height)
+            {
+                if height == 9 {
+                    peaks[p] = HashSet::from([p]);
+                    paths[p] = 1;
+                } else {
+                    for n in limits
+                        .neighbors(p, ArrayOrientation::Linear)
+                        .into_iter()
+                        .filter(|n| heights[*n] == height + 1)
+                    {
+                        let above = peaks[n].clone();
+                        peaks[p].extend(above);
+                        paths[p] += paths[n];
+                    }
                 }
-            })
-            .collect();
+            }
+        }
         LavaProductionFacility {
-            map,
-            starting_positions,
+            heights,
+            limits,
+            peaks,
+            paths,
         }
     }
 
-    fn routes<'a>(&self, node: &Topogaphy) -> HashSet<Vec<Topogaphy>> {
-        match node.height {
-            9 => HashSet::from([vec![*node]]),
-            _ => node
-                .ascending_neighbors()
-                .into_iter()
-                .filter(|n| self.map.contains(n))
-                .fold(HashSet::<Vec<Topogaphy>>::new(), |mut acc, n| {
-                    self.routes(&n).into_iter().for_each(|v| {
-                        let mut path = v.clone();
-                        path.push(*node);
-                        acc.insert(path);
-                    });
-                    acc
-                }),
+    fn trailheads(&self) -> Vec<Trailhead> {
+        self.limits
+            .all_points()
+            .into_iter()
+            .filter(|p| self.heights[*p] == 0)
+            .map(|point| Trailhead {
+                point,
+                score: self.peaks[point].len(),
+                rating: self.paths[point],
+            })
+            .collect()
+    }
+
+    /// Draws every point on a hiking trail from `start`, anything else is left as `.`
+    fn render_trails(&self, start: Point) -> String {
+        let mut on_trail: HashSet<Point> = HashSet::new();
+        let mut discover = vec![start];
+        while let Some(p) = discover.pop() {
+            if self.paths[p] > 0 && on_trail.insert(p) {
+                discover.extend(
+                    self.limits
+                        .neighbors(p, ArrayOrientation::Linear)
+                        .into_iter()
+                        .filter(|n| self.heights[*n] == self.heights[p] + 1),
+                );
+            }
         }
+        self.limits
+            .vertical_range()
+            .map(|i| {
+                self.limits
+                    .horizontal_range()
+                    .map(|j| match on_trail.contains(&(i, j)) {
+                        true => char::from_digit(self.heights[(i, j)], 10).unwrap(),
+                        false => '.',
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
     }
 }
 
 pub fn part1(input: String) -> Result<String, error::Error> {
-    let map: LavaProductionFacility = LavaProductionFacility::from_input(&input);
-
-    println!("trailheads: {}", map.starting_positions.len());
-    let result: usize = map.starting_positions[..]
-        .into_iter()
-        .map(|p| {
-            let result = map.routes(p);
-            let distinct_9s: HashSet<Topogaphy> =
-                result.iter().fold(HashSet::new(), |mut acc, y| {
-                    assert!(y[0].height == 9);
-                    acc.insert(y[0]);
-                    acc
-                });
-            distinct_9s.len()
-        })
-        .sum();
+    let map = LavaProductionFacility::from_input(&input);
+    let result: usize = map.trailheads().iter().map(|t| t.score).sum();
     Ok(result.to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let map: LavaProductionFacility = LavaProductionFacility::from_input(&input);
+    let map = LavaProductionFacility::from_input(&input);
+    let result: usize = map.trailheads().iter().map(|t| t.rating).sum();
+    Ok(result.to_string())
+}
 
-    println!("trailheads: {}", map.starting_positions.len());
-    let result: usize = map.starting_positions[..]
-        .into_iter()
-        .map(|p| {
-            let result = map.routes(p);
-            result.len()
+/// Lists every trailhead with its score and rating and draws the trails from the highest
+/// rated one.
+pub fn debug(input: String, _args: &[String]) -> Result<String, error::Error> {
+    let map = LavaProductionFacility::from_input(&input);
+    let trailheads = map.trailheads();
+    let listing = trailheads
+        .iter()
+        .map(|t| {
+            format!(
+                "trailhead {:?} score {} rating {}",
+                t.point, t.score, t.rating
+            )
         })
-        .sum();
-    Ok(result.to_string())
+        .join("\n");
+    let best = trailheads
+        .iter()
+        .max_by_key(|t| t.rating)
+        .map(|t| map.render_trails(t.point))
+        .unwrap_or_default();
+    Ok(format!("{listing}\n\n{best}"))
 }
 
 #[cfg(test)]
@@ -150,4 +171,27 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "81")
     }
+    #[test]
+    fn test_impassable_tiles() {
+        let input = "..90..9
+...1.98
+...2..7
+6543456
+765.987
+876....
+987....";
+        let map = LavaProductionFacility::from_input(input);
+        assert_eq!(map.trailheads()[0].rating, 13);
+        assert_eq!(map.trailheads()[0].score, 4);
+    }
+    #[test]
+    fn test_render_trails() {
+        let input = "0123
+1234
+8765
+9876";
+        let map = LavaProductionFacility::from_input(input);
+        assert_eq!(map.render_trails((0, 0)), "0123\n1234\n8765\n9876");
+        assert_eq!(map.trailheads()[0].rating, 16);
+    }
 }
@@ -1,4 +1,10 @@
-use memoize::memoize;
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+    ops::AddAssign,
+};
+
+use num::{BigUint, One, Zero};
 
 use crate::error;
 
@@ -8,41 +14,129 @@ use nom::{
     multi::separated_list1,
     IResult,
 };
-fn numbers(s: &str) -> IResult<&str, Vec<usize>> {
-    separated_list1(space1, map_res(digit1, str::parse::<usize>))(s)
-}
-
-#[memoize]
-fn get_length(number: usize, depth: usize) -> usize {
-    if depth == 0 {
-        1
-    } else {
-        match number.to_string().as_str() {
-            "0" => get_length(1, depth - 1),
-            x if x.len() % 2 == 0 => {
-                let first = x[0..x.len() / 2].parse().unwrap();
-                let second = x[x.len() / 2..x.len()].parse().unwrap();
-                // println!("{},{} = {}", &first, &second, number);
-                get_length(first, depth - 1) + get_length(second, depth - 1)
+fn numbers(s: &str) -> IResult<&str, Vec<u64>> {
+    separated_list1(space1, map_res(digit1, str::parse::<u64>))(s.trim())
+}
+
+/// The two halves of the digits of `n`, None if it has an odd number of digits
+fn split(n: u64) -> Option<(u64, u64)> {
+    let digits = n.checked_ilog10()? + 1;
+    if digits % 2 == 1 {
+        return None;
+    }
+    let half = 10u64.pow(digits / 2);
+    Some((n / half, n % half))
+}
+
+/// How many stones have each number engraved on them. Stones never affect their neighbours
+/// so their order is thrown away and stones with the same number change together.
+/// The count type only needs to be wider than u64 once there are more than 2^64 stones.
+#[derive(Debug, Clone)]
+struct Stones<C = u64> {
+    counts: HashMap<u64, C>,
+}
+
+impl<C> Stones<C>
+where
+    C: Clone + Zero + One + for<'a> AddAssign<&'a C>,
+{
+    fn from_numbers(numbers: &[u64]) -> Stones<C> {
+        let mut counts: HashMap<u64, C> = HashMap::new();
+        for n in numbers {
+            *counts.entry(*n).or_insert_with(C::zero) += &C::one();
+        }
+        Stones { counts }
+    }
+
+    fn blink(&self) -> Stones<C> {
+        let mut counts: HashMap<u64, C> = HashMap::with_capacity(self.counts.len());
+        let mut add = |n: u64, c: &C| *counts.entry(n).or_insert_with(C::zero) += c;
+        for (n, c) in &self.counts {
+            match (n, split(*n)) {
+                (0, _) => add(1, c),
+                (_, Some((left, right))) => {
+                    add(left, c);
+                    add(right, c);
+                }
+                (n, None) => add(n * 2024, c),
+            }
+        }
+        Stones { counts }
+    }
+
+    /// this arrangement followed by the arrangement after each blink
+    fn generations(&self) -> impl Iterator<Item = Stones<C>> {
+        iter::successors(Some(self.clone()), |s| Some(s.blink()))
+    }
+
+    fn count(&self) -> C {
+        let mut total = C::zero();
+        for c in self.counts.values() {
+            total += c;
+        }
+        total
+    }
+
+    fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    fn count_after(&self, blinks: usize) -> C {
+        self.generations().nth(blinks).unwrap().count()
+    }
+
+    /// The first blink that doesn't engrave a number that hasn't been seen before, and how
+    /// many numbers have been seen by then.
+    /// Every number seen after that is the child of a number already seen so the set of
+    /// numbers never grows again.
+    fn saturation(&self) -> (usize, usize) {
+        let mut seen: HashSet<u64> = HashSet::new();
+        for (blink, stones) in self.generations().enumerate() {
+            let before = seen.len();
+            seen.extend(stones.counts.keys());
+            if blink > 0 && seen.len() == before {
+                return (blink, seen.len());
             }
-            _ => get_length(number * 2024, depth - 1),
         }
+        unreachable!("generations never ends")
     }
 }
 
 pub fn part1(input: String) -> Result<String, error::Error> {
-    let pebbles = numbers(&input).ok().unwrap().1;
-    let result: usize = pebbles.into_iter().map(|p| get_length(p, 25)).sum();
-
-    Ok(result.to_string())
+    let stones: Stones = Stones::from_numbers(&numbers(&input).ok().unwrap().1);
+    Ok(stones.count_after(25).to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let pebbles = numbers(&input).ok().unwrap().1;
-
-    let result: usize = pebbles.into_iter().map(|p| get_length(p, 75)).sum();
+    let stones: Stones = Stones::from_numbers(&numbers(&input).ok().unwrap().1);
+    Ok(stones.count_after(75).to_string())
+}
 
-    Ok(result.to_string())
+/// Lists the number of stones and distinct numbers after each blink up to the given number of
+/// blinks, 75 by default, and when the distinct numbers stop growing.
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let blinks = match args.first() {
+        Some(b) => b
+            .parse::<usize>()
+            .map_err(|_| error::Error::InvalidArgument(b.to_string()))?,
+        None => 75,
+    };
+    let stones: Stones<BigUint> = Stones::from_numbers(&numbers(&input).ok().unwrap().1);
+    let mut lines: Vec<String> = stones
+        .generations()
+        .take(blinks + 1)
+        .enumerate()
+        .map(|(blink, s)| {
+            format!(
+                "blink {blink}: {} stones {} distinct",
+                s.count(),
+                s.distinct()
+            )
+        })
+        .collect();
+    let (blink, seen) = stones.saturation();
+    lines.push(format!("no new numbers after blink {blink}, {seen} seen"));
+    Ok(lines.join("\n"))
 }
 
 #[cfg(test)]
@@ -56,4 +150,49 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "55312")
     }
+    #[test]
+    fn test_part2() {
+        let input = INPUT.to_string();
+        let result = part2(input);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "65601038650482")
+    }
+    #[test]
+    fn test_split() {
+        assert_eq!(split(0), None);
+        assert_eq!(split(7), None);
+        assert_eq!(split(10), Some((1, 0)));
+        assert_eq!(split(1000), Some((10, 0)));
+        assert_eq!(split(253000), Some((253, 0)));
+        assert_eq!(split(999), None);
+    }
+    #[test]
+    fn test_blink() {
+        let stones: Stones = Stones::from_numbers(&[0, 1, 10, 99, 999]);
+        let after = stones.blink();
+        assert_eq!(after.count(), 7);
+        let mut numbers: Vec<u64> = after.counts.keys().copied().collect();
+        numbers.sort();
+        assert_eq!(numbers, vec![0, 1, 9, 2024, 2021976]);
+        assert_eq!(after.counts[&9], 2);
+    }
+    #[test]
+    fn test_many_blinks() {
+        let small: Stones = Stones::from_numbers(&[125, 17]);
+        let big: Stones<BigUint> = Stones::from_numbers(&[125, 17]);
+        assert_eq!(big.count_after(75), BigUint::from(small.count_after(75)));
+        assert!(big.count_after(1000) > BigUint::from(u128::MAX));
+    }
+    #[test]
+    fn test_saturation() {
+        let stones: Stones = Stones::from_numbers(&[125, 17]);
+        let (blink, seen) = stones.saturation();
+        let all_seen: HashSet<u64> = stones
+            .generations()
+            .take(blink + 50)
+            .flat_map(|s| s.counts.into_keys())
+            .collect();
+        assert_eq!(all_seen.len(), seen);
+        assert!(stones.generations().nth(blink + 50).unwrap().distinct() <= seen);
+    }
 }
@@ -1,3 +1,5 @@
+use num::integer::{ExtendedGcd, Integer};
+
 use crate::error;
 use nom::{
     bytes::complete::tag,
@@ -28,20 +30,131 @@ impl Machine {
             prize: t.2,
         })
     }
-    fn cramers_method(&self, prize_add: i64) -> Option<i64> {
-        // A = (p_x*b_y - prize_y*b_x) / (a_x*b_y - a_y*b_x)
-        // B = (a_x*p_y - a_y*p_x) / (a_x*b_y - a_y*b_x)
-        let prize = (prize_add + self.prize.0, prize_add + self.prize.1);
-        let a_num = (prize.0 * self.b.1) - (prize.1 * self.b.0);
-        let b_num = (self.a.0 * prize.1) - (self.a.1 * prize.0);
-        let denom = (self.a.0 * self.b.1) - (self.a.1 * self.b.0);
-
-        if (a_num % denom == 0) & (b_num % denom == 0) {
-            let a = a_num / denom;
-            let b = b_num / denom;
-            Some(a * 3 + b)
+    /// The cheapest presses that win the prize moved out by `offset` on each axis, pressing each
+    /// button no more than `limit` times if there is a limit.
+    fn solve(&self, offset: i64, limit: Option<i64>) -> Result<Presses, Unwinnable> {
+        let prize = (self.prize.0 + offset, self.prize.1 + offset);
+        let det = cross(self.a, self.b);
+        if det == 0 {
+            return self.solve_collinear(prize, limit);
+        }
+        // Cramer's rule, there is only one way to reach the prize
+        let a_num = cross(prize, self.b);
+        let b_num = cross(self.a, prize);
+        if a_num % det != 0 || b_num % det != 0 {
+            return Err(Unwinnable::NotWhole);
+        }
+        let presses = Presses {
+            a: a_num / det,
+            b: b_num / det,
+        };
+        if presses.a < 0 || presses.b < 0 {
+            Err(Unwinnable::Negative)
+        } else if limit.is_some_and(|l| presses.a > l || presses.b > l) {
+            Err(Unwinnable::TooManyPresses)
         } else {
-            None
+            Ok(presses)
+        }
+    }
+
+    /// Both buttons move the claw along the same line so the prize has to be on it, then there
+    /// can be many ways to reach it and the cheapest is wanted.
+    fn solve_collinear(
+        &self,
+        prize: (i64, i64),
+        limit: Option<i64>,
+    ) -> Result<Presses, Unwinnable> {
+        let direction = if self.a != (0, 0) { self.a } else { self.b };
+        if direction == (0, 0) && prize != (0, 0) || cross(direction, prize) != 0 {
+            return Err(Unwinnable::OffLine);
+        }
+        // on the line one axis is enough, use one the buttons move along
+        let (a, b, p) = match direction.0 {
+            0 => (self.a.1, self.b.1, prize.1),
+            _ => (self.a.0, self.b.0, prize.0),
+        };
+        let within_limit = |presses: Presses| match limit {
+            Some(l) if presses.a > l || presses.b > l => Err(Unwinnable::TooManyPresses),
+            _ => Ok(presses),
+        };
+        match (a, b) {
+            (0, 0) => return Ok(Presses { a: 0, b: 0 }),
+            (0, b) | (b, 0) if p % b != 0 => return Err(Unwinnable::NotWhole),
+            (0, b) => return within_limit(Presses { a: 0, b: p / b }),
+            (a, 0) => return within_limit(Presses { a: p / a, b: 0 }),
+            _ => (),
+        }
+        let ExtendedGcd { gcd, x, y, .. } = a.extended_gcd(&b);
+        if p % gcd != 0 {
+            return Err(Unwinnable::NotWhole);
+        }
+        // every solution is a = a0 + k * step_a, b = b0 - k * step_b for some whole k
+        let (a0, b0) = (x * (p / gcd), y * (p / gcd));
+        let (step_a, step_b) = (b / gcd, a / gcd);
+        let mut lowest_k = Integer::div_ceil(&-a0, &step_a);
+        let mut highest_k = Integer::div_floor(&b0, &step_b);
+        if lowest_k > highest_k {
+            return Err(Unwinnable::Negative);
+        }
+        if let Some(l) = limit {
+            lowest_k = lowest_k.max(Integer::div_ceil(&(b0 - l), &step_b));
+            highest_k = highest_k.min(Integer::div_floor(&(l - a0), &step_a));
+            if lowest_k > highest_k {
+                return Err(Unwinnable::TooManyPresses);
+            }
+        }
+        // the cost changes by the same amount with each step of k so one end is cheapest
+        let k = match A_COST * step_a - B_COST * step_b {
+            slope if slope < 0 => highest_k,
+            _ => lowest_k,
+        };
+        Ok(Presses {
+            a: a0 + k * step_a,
+            b: b0 - k * step_b,
+        })
+    }
+}
+
+fn cross(u: (i64, i64), v: (i64, i64)) -> i64 {
+    u.0 * v.1 - u.1 * v.0
+}
+
+const A_COST: i64 = 3;
+const B_COST: i64 = 1;
+const PRESS_LIMIT: i64 = 100;
+const PRIZE_OFFSET: i64 = 10000000000000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Presses {
+    a: i64,
+    b: i64,
+}
+
+impl Presses {
+    fn cost(&self) -> i64 {
+        A_COST * self.a + B_COST * self.b
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unwinnable {
+    NotWhole,
+    Negative,
+    OffLine,
+    TooManyPresses,
+}
+
+impl Unwinnable {
+    fn reason(&self) -> &'static str {
+        match self {
+            Unwinnable::NotWhole => "the prize can only be reached with part of a press",
+            Unwinnable::Negative => {
+                "the prize can only be reached by pressing a button less than zero times"
+            }
+            Unwinnable::OffLine => {
+                "the buttons only move the claw along a line that misses the prize"
+            }
+            Unwinnable::TooManyPresses => "the prize needs a button pressed more than the limit",
         }
     }
 }
@@ -73,21 +186,39 @@ fn machines(s: &str) -> Vec<Machine> {
     machines.ok().unwrap().1
 }
 
+fn total_cost(input: &str, offset: i64, limit: Option<i64>) -> i64 {
+    machines(input)
+        .iter()
+        .flat_map(|m| m.solve(offset, limit))
+        .map(|p| p.cost())
+        .sum()
+}
+
 pub fn part1(input: String) -> Result<String, error::Error> {
-    let machines = machines(&input);
-    // dbg!(&machines);
-    let result: i64 = machines.into_iter().flat_map(|m| m.cramers_method(0)).sum();
-    Ok(result.to_string())
+    Ok(total_cost(&input, 0, Some(PRESS_LIMIT)).to_string())
 }
 
 pub fn part2(input: String) -> Result<String, error::Error> {
-    let machines = machines(&input);
-    // dbg!(&machines);
-    let result: i64 = machines
-        .into_iter()
-        .flat_map(|m| m.cramers_method(10000000000000))
-        .sum();
-    Ok(result.to_string())
+    Ok(total_cost(&input, PRIZE_OFFSET, None).to_string())
+}
+
+/// Lists the presses and cost to win each machine, or why it can't be won, for part 2 unless
+/// given `1`.
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let (offset, limit) = match args.first().map(String::as_str) {
+        Some("1") => (0, Some(PRESS_LIMIT)),
+        Some("2") | None => (PRIZE_OFFSET, None),
+        Some(x) => return Err(error::Error::InvalidArgument(x.to_string())),
+    };
+    Ok(machines(&input)
+        .iter()
+        .enumerate()
+        .map(|(i, m)| match m.solve(offset, limit) {
+            Ok(p) => format!("machine {}: {} A {} B costs {}", i + 1, p.a, p.b, p.cost()),
+            Err(u) => format!("machine {}: unwinnable, {}", i + 1, u.reason()),
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
 }
 
 #[cfg(test)]
@@ -122,4 +253,65 @@ Prize: X=18641, Y=10279";
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "875318608908")
     }
+    #[test]
+    fn test_presses() {
+        let machines = machines(INPUT);
+        assert_eq!(
+            machines[0].solve(0, Some(PRESS_LIMIT)),
+            Ok(Presses { a: 80, b: 40 })
+        );
+        assert_eq!(machines[0].solve(0, Some(PRESS_LIMIT)).unwrap().cost(), 280);
+        assert_eq!(
+            machines[1].solve(0, Some(PRESS_LIMIT)),
+            Err(Unwinnable::NotWhole)
+        );
+        assert!(machines[1].solve(PRIZE_OFFSET, None).is_ok());
+    }
+    #[test]
+    fn test_unwinnable() {
+        let machine = |a, b, prize| Machine { a, b, prize };
+        assert_eq!(
+            machine((2, 1), (1, 2), (1, 5)).solve(0, None),
+            Err(Unwinnable::Negative)
+        );
+        assert_eq!(
+            machine((1, 0), (0, 1), (150, 5)).solve(0, Some(PRESS_LIMIT)),
+            Err(Unwinnable::TooManyPresses)
+        );
+        assert_eq!(
+            machine((1, 0), (0, 1), (150, 5)).solve(0, None),
+            Ok(Presses { a: 150, b: 5 })
+        );
+    }
+    #[test]
+    fn test_collinear() {
+        let machine = |a, b, prize| Machine { a, b, prize };
+        // B is cheaper per step
+        assert_eq!(
+            machine((1, 1), (3, 3), (9, 9)).solve(0, None),
+            Ok(Presses { a: 0, b: 3 })
+        );
+        // A is cheaper per step but can't get all the way
+        assert_eq!(
+            machine((10, 10), (1, 1), (23, 23)).solve(0, None),
+            Ok(Presses { a: 2, b: 3 })
+        );
+        // the limit forces more of the expensive button
+        assert_eq!(
+            machine((1, 2), (3, 6), (303, 606)).solve(0, Some(PRESS_LIMIT)),
+            Ok(Presses { a: 3, b: 100 })
+        );
+        assert_eq!(
+            machine((1, 1), (3, 3), (9, 8)).solve(0, None),
+            Err(Unwinnable::OffLine)
+        );
+        assert_eq!(
+            machine((2, 2), (4, 4), (5, 5)).solve(0, None),
+            Err(Unwinnable::NotWhole)
+        );
+        assert_eq!(
+            machine((0, 0), (0, 3), (0, 9)).solve(0, None),
+            Ok(Presses { a: 0, b: 3 })
+        );
+    }
 }
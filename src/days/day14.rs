@@ -1,31 +1,213 @@
+use itertools::Itertools;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{i64 as signed, line_ending, space1},
+    multi::separated_list1,
+    sequence::{preceded, separated_pair},
+    IResult,
+};
+
 use crate::error;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+const WIDTH: i64 = 101;
+const HEIGHT: i64 = 103;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Robot {
+    position: (i64, i64),
+    velocity: (i64, i64),
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+impl Robot {
+    fn build_from(t: ((i64, i64), (i64, i64))) -> Robot {
+        Robot {
+            position: t.0,
+            velocity: t.1,
+        }
+    }
+    /// position after `steps` seconds, wrapping around the edges of a `width` x `height` room
+    fn position_after(&self, steps: i64, width: i64, height: i64) -> (i64, i64) {
+        (
+            (self.position.0 + self.velocity.0 * steps).rem_euclid(width),
+            (self.position.1 + self.velocity.1 * steps).rem_euclid(height),
+        )
+    }
+}
+
+fn pair(s: &str) -> IResult<&str, (i64, i64)> {
+    separated_pair(signed, tag(","), signed)(s)
+}
+fn robot(s: &str) -> IResult<&str, Robot> {
+    let (s, t) = separated_pair(preceded(tag("p="), pair), space1, preceded(tag("v="), pair))(s)?;
+    Ok((s, Robot::build_from(t)))
+}
+fn robots(s: &str) -> Vec<Robot> {
+    separated_list1(line_ending, robot)(s.trim())
+        .ok()
+        .unwrap()
+        .1
+}
+
+struct Room {
+    robots: Vec<Robot>,
+    width: i64,
+    height: i64,
+}
+
+impl Room {
+    fn from_input(input: &str, width: i64, height: i64) -> Room {
+        Room {
+            robots: robots(input),
+            width,
+            height,
+        }
+    }
+
+    fn positions(&self, steps: i64) -> Vec<(i64, i64)> {
+        self.robots
+            .iter()
+            .map(|r| r.position_after(steps, self.width, self.height))
+            .collect()
+    }
+
+    /// product of the robot counts in each quadrant, robots on the middle lines are not counted
+    fn safety_factor(&self, steps: i64) -> usize {
+        let (mid_x, mid_y) = (self.width / 2, self.height / 2);
+        let mut quadrants = [0usize; 4];
+        for (x, y) in self.positions(steps) {
+            if x == mid_x || y == mid_y {
+                continue;
+            }
+            let q = match (x < mid_x, y < mid_y) {
+                (true, true) => 0,
+                (false, true) => 1,
+                (true, false) => 2,
+                (false, false) => 3,
+            };
+            quadrants[q] += 1;
+        }
+        quadrants.iter().product()
+    }
+
+    fn variance(values: impl Iterator<Item = i64> + Clone) -> f64 {
+        let n = values.clone().count() as f64;
+        let mean = values.clone().sum::<i64>() as f64 / n;
+        values.map(|v| (v as f64 - mean).powi(2)).sum::<f64>() / n
+    }
+
+    /// the picture is drawn when the robots bunch together, so look for the step where both axes
+    /// have their lowest variance. x repeats every `width` steps and y every `height` steps, so
+    /// the best step for each axis is found independently then combined with the chinese
+    /// remainder theorem.
+    fn tree_step(&self) -> i64 {
+        let best_x = (0..self.width)
+            .min_by(|a, b| {
+                let va = Room::variance(self.positions(*a).into_iter().map(|p| p.0));
+                let vb = Room::variance(self.positions(*b).into_iter().map(|p| p.0));
+                va.total_cmp(&vb)
+            })
+            .unwrap();
+        let best_y = (0..self.height)
+            .min_by(|a, b| {
+                let va = Room::variance(self.positions(*a).into_iter().map(|p| p.1));
+                let vb = Room::variance(self.positions(*b).into_iter().map(|p| p.1));
+                va.total_cmp(&vb)
+            })
+            .unwrap();
+        (0..self.height)
+            .map(|k| best_x + k * self.width)
+            .find(|t| t % self.height == best_y)
+            .unwrap()
+    }
+
+    fn render(&self, steps: i64) -> String {
+        let positions = self.positions(steps);
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(
+                        |x| match positions.iter().filter(|p| **p == (x, y)).count() {
+                            0 => '.',
+                            n if n < 10 => char::from_digit(n as u32, 10).unwrap(),
+                            _ => '+',
+                        },
+                    )
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let room = Room::from_input(&input, WIDTH, HEIGHT);
+    Ok(room.safety_factor(100).to_string())
+}
+
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let room = Room::from_input(&input, WIDTH, HEIGHT);
+    Ok(room.tree_step().to_string())
+}
+
+/// Draws the robots after the given number of steps, by default the step the tree appears.
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let room = Room::from_input(&input, WIDTH, HEIGHT);
+    let step = match args.first() {
+        Some(s) => s
+            .parse::<i64>()
+            .map_err(|_| error::Error::InvalidArgument(s.to_string()))?,
+        None => room.tree_step(),
+    };
+    Ok(format!("step {step}\n{}", room.render(step)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2
+p=2,0 v=2,-1
+p=0,0 v=1,3
+p=3,0 v=-2,-2
+p=7,6 v=-1,-3
+p=3,0 v=-1,-2
+p=9,3 v=2,3
+p=7,3 v=-1,2
+p=2,4 v=2,-3
+p=9,5 v=-3,-3";
     #[test]
     fn test_part1() {
-        let input = INPUT.to_string();
-        let result = part1(input);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        let room = Room::from_input(INPUT, 11, 7);
+        assert_eq!(room.safety_factor(100), 12)
+    }
+    #[test]
+    fn test_robot_wraps() {
+        let room = Room::from_input("p=2,4 v=2,-3", 11, 7);
+        assert_eq!(room.positions(5), vec![(1, 3)])
+    }
+    #[test]
+    fn test_render() {
+        let room = Room::from_input(INPUT, 11, 7);
+        let expected = "......2..1.
+...........
+1..........
+.11........
+.....1.....
+...12......
+.1....1....";
+        assert_eq!(room.render(100), expected)
     }
-    #[ignore]
     #[test]
     fn test_part2() {
-        let input = INPUT.to_string();
-        let result = part2(input);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        // robots gathered into a tight block at step 10 are found by the variance heuristic
+        let input = (0..9)
+            .map(|i| {
+                let (x, y) = (5 + i % 3, 4 + i / 3);
+                let v = (i + 2, 2 * i + 1);
+                format!("p={},{} v={},{}", x - 10 * v.0, y - 10 * v.1, v.0, v.1)
+            })
+            .join("\n");
+        let room = Room::from_input(&input, 11, 13);
+        assert_eq!(room.tree_step(), 10)
     }
 }
@@ -1,31 +1,200 @@
-use crate::error;
+use std::collections::HashSet;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+use array2d::Array2D;
+
+use crate::{
+    error,
+    utils::{find_in_grid, grid_from_input, ArrayUtils2D, Direction},
+};
+
+struct Warehouse {
+    grid: Array2D<char>,
+    limits: ArrayUtils2D,
+    robot: (usize, usize),
+}
+
+impl Warehouse {
+    fn from_map(map: &str) -> Warehouse {
+        let grid = grid_from_input(map);
+        let limits = ArrayUtils2D::from_array(&grid);
+        let robot = find_in_grid(&grid, '@').unwrap();
+        Warehouse {
+            grid,
+            limits,
+            robot,
+        }
+    }
+
+    /// every box gets twice as wide, as does everything else apart from the robot
+    fn widen(map: &str) -> String {
+        map.chars()
+            .map(|c| match c {
+                '#' => "##".to_string(),
+                'O' => "[]".to_string(),
+                '.' => "..".to_string(),
+                '@' => "@.".to_string(),
+                x => x.to_string(),
+            })
+            .collect()
+    }
+
+    /// Collects every cell that has to move for the robot to step in direction `d`,
+    /// in the order they were discovered. Wide boxes pushed vertically pull in their other
+    /// half so a single push can fan out into a tree of boxes.
+    /// Returns None if anything in the chain is up against a wall.
+    fn pushed_cells(&self, d: Direction) -> Option<Vec<(usize, usize)>> {
+        let mut cells = vec![self.robot];
+        let mut seen: HashSet<(usize, usize)> = HashSet::from([self.robot]);
+        let mut i = 0;
+        while i < cells.len() {
+            let next = self.limits.step(cells[i], d)?;
+            i += 1;
+            let mut to_add = match self.grid[next] {
+                '#' => return None,
+                'O' => vec![next],
+                '[' if d.is_vertical() => vec![next, (next.0, next.1 + 1)],
+                ']' if d.is_vertical() => vec![next, (next.0, next.1 - 1)],
+                '[' | ']' => vec![next],
+                _ => vec![],
+            };
+            to_add.retain(|p| seen.insert(*p));
+            cells.extend(to_add);
+        }
+        Some(cells)
+    }
+
+    /// moves the robot and anything it pushes, or nothing at all if the push is blocked
+    fn move_robot(&mut self, d: Direction) {
+        if let Some(cells) = self.pushed_cells(d) {
+            for cell in cells.into_iter().rev() {
+                let next = self.limits.step(cell, d).unwrap();
+                self.grid[next] = self.grid[cell];
+                self.grid[cell] = '.';
+            }
+            self.robot = self.limits.step(self.robot, d).unwrap();
+        }
+    }
+
+    fn gps_sum(&self) -> usize {
+        self.grid
+            .enumerate_row_major()
+            .filter(|(_, c)| matches!(c, 'O' | '['))
+            .map(|((i, j), _)| 100 * i + j)
+            .sum()
+    }
+
+    #[cfg(test)]
+    fn render(&self) -> String {
+        self.grid
+            .as_rows()
+            .into_iter()
+            .map(|r| r.into_iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn parse_input(input: &str) -> (&str, Vec<Direction>) {
+    let (map, moves) = input.split_once("\n\n").unwrap();
+    (map, moves.chars().flat_map(Direction::from_char).collect())
+}
+
+fn simulate(map: &str, moves: &[Direction]) -> Warehouse {
+    let mut warehouse = Warehouse::from_map(map);
+    for d in moves {
+        warehouse.move_robot(*d);
+    }
+    warehouse
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let (map, moves) = parse_input(&input);
+    let warehouse = simulate(map, &moves);
+    Ok(warehouse.gps_sum().to_string())
+}
+
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let (map, moves) = parse_input(&input);
+    let warehouse = simulate(&Warehouse::widen(map), &moves);
+    Ok(warehouse.gps_sum().to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const SMALL_INPUT: &str = "########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<";
+    const INPUT: &str = "##########
+#..O..O.O#
+#......O.#
+#.OO..O.O#
+#..O@..O.#
+#O#..O...#
+#O..O..O.#
+#.OO.O.OO#
+#....O...#
+##########
+
+<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
+    const WIDE_INPUT: &str = "#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^";
+
+    #[test]
+    fn test_part1_small() {
+        let input = SMALL_INPUT.to_string();
+        let result = part1(input);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "2028")
+    }
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "10092")
+    }
+    #[test]
+    fn test_wide_push_tree() {
+        let (map, moves) = parse_input(WIDE_INPUT);
+        let warehouse = simulate(&Warehouse::widen(map), &moves);
+        let expected = "##############
+##...[].##..##
+##...@.[]...##
+##....[]....##
+##..........##
+##..........##
+##############";
+        assert_eq!(warehouse.render(), expected)
     }
-    #[ignore]
     #[test]
     fn test_part2() {
         let input = INPUT.to_string();
         let result = part2(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "9021")
     }
 }
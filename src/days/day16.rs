@@ -1,31 +1,216 @@
-use crate::error;
+use std::collections::{HashMap, HashSet};
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+use array2d::Array2D;
+use pathfinding::prelude::dijkstra_all;
+
+use crate::{
+    error,
+    utils::{find_in_grid, grid_from_input, ArrayUtils2D, Direction},
+};
+
+const STEP_COST: usize = 1;
+const TURN_COST: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Reindeer {
+    position: (usize, usize),
+    facing: Direction,
+}
+
+struct Maze {
+    grid: Array2D<char>,
+    limits: ArrayUtils2D,
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+impl Maze {
+    fn from_input(input: &str) -> Maze {
+        let grid = grid_from_input(input);
+        let limits = ArrayUtils2D::from_array(&grid);
+        let start = find_in_grid(&grid, 'S').unwrap();
+        let end = find_in_grid(&grid, 'E').unwrap();
+        Maze {
+            grid,
+            limits,
+            start,
+            end,
+        }
+    }
+
+    fn successors(&self, r: &Reindeer) -> Vec<(Reindeer, usize)> {
+        let mut moves = vec![
+            (
+                Reindeer {
+                    position: r.position,
+                    facing: r.facing.right_turn(),
+                },
+                TURN_COST,
+            ),
+            (
+                Reindeer {
+                    position: r.position,
+                    facing: r.facing.left_turn(),
+                },
+                TURN_COST,
+            ),
+        ];
+        if let Some(next) = self.limits.step(r.position, r.facing) {
+            if self.grid[next] != '#' {
+                moves.push((
+                    Reindeer {
+                        position: next,
+                        facing: r.facing,
+                    },
+                    STEP_COST,
+                ));
+            }
+        }
+        moves
+    }
+
+    /// The lowest score to reach every reachable state starting from any state in `from`.
+    fn scores(&self, from: &[Reindeer]) -> HashMap<Reindeer, usize> {
+        // None stands for a start joined to every state in `from` at no cost
+        let successors = |r: &Option<Reindeer>| -> Vec<(Option<Reindeer>, usize)> {
+            match r {
+                None => from.iter().map(|f| (Some(*f), 0)).collect(),
+                Some(r) => self
+                    .successors(r)
+                    .into_iter()
+                    .map(|(next, cost)| (Some(next), cost))
+                    .collect(),
+            }
+        };
+        dijkstra_all(&None, successors)
+            .into_iter()
+            .filter_map(|(r, (_, score))| Some((r?, score)))
+            .collect()
+    }
+
+    /// The lowest score from start to end along with every tile that sits on at least one
+    /// path achieving that score.
+    /// Any path read backwards is a path for a reindeer facing the other way at the same
+    /// score, so searching out from the end turned around gives the score left to reach the
+    /// end from every state. A state is on a best path when the scores either side add up to
+    /// the best score, which avoids listing the paths themselves as there can be
+    /// exponentially many.
+    fn solve(&self) -> (usize, HashSet<(usize, usize)>) {
+        let start = Reindeer {
+            position: self.start,
+            facing: Direction::East,
+        };
+        let ends: Vec<Reindeer> = Direction::ALL
+            .iter()
+            .map(|facing| Reindeer {
+                position: self.end,
+                facing: *facing,
+            })
+            .collect();
+        let from_start = self.scores(&[start]);
+        let to_end = self.scores(&ends);
+        let score = *ends.iter().filter_map(|r| from_start.get(r)).min().unwrap();
+        let tiles = from_start
+            .iter()
+            .filter(|(r, s)| {
+                let turned = Reindeer {
+                    position: r.position,
+                    facing: r.facing.opposite(),
+                };
+                to_end.get(&turned).is_some_and(|e| *s + e == score)
+            })
+            .map(|(r, _)| r.position)
+            .collect();
+        (score, tiles)
+    }
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let (score, _) = Maze::from_input(&input).solve();
+    Ok(score.to_string())
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let (_, tiles) = Maze::from_input(&input).solve();
+    Ok(tiles.len().to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+    const SECOND_INPUT: &str = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "7036")
+    }
+    #[test]
+    fn test_part1_second() {
+        let input = SECOND_INPUT.to_string();
+        let result = part1(input);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "11048")
     }
-    #[ignore]
     #[test]
     fn test_part2() {
         let input = INPUT.to_string();
         let result = part2(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "45")
+    }
+    #[test]
+    fn test_part2_second() {
+        let input = SECOND_INPUT.to_string();
+        let result = part2(input);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "64")
+    }
+    #[test]
+    fn test_many_best_paths() {
+        // every block can be passed above or below for the same score giving 2^40 best paths
+        let blocks = 40;
+        let wall = "#".repeat(4 * blocks + 3);
+        let side = format!("##{}#", "...#".repeat(blocks));
+        let middle = format!("#S{}E#", ".#..".repeat(blocks).strip_suffix('.').unwrap());
+        let input = [wall.as_str(), &side, &middle, &side, &wall].join("\n");
+        let (score, tiles) = Maze::from_input(&input).solve();
+        assert_eq!(score, blocks * (4 * TURN_COST + 6 * STEP_COST));
+        assert_eq!(
+            tiles.len(),
+            input.chars().filter(|c| ".SE".contains(*c)).count()
+        );
     }
 }
@@ -1,31 +1,287 @@
+use itertools::Itertools;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alpha1, multispace0, multispace1, u64 as unsigned, u8 as opcode},
+    multi::separated_list1,
+    sequence::{delimited, tuple},
+    IResult,
+};
+
 use crate::error;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl Instruction {
+    fn from_opcode(code: u8) -> Instruction {
+        match code {
+            0 => Instruction::Adv,
+            1 => Instruction::Bxl,
+            2 => Instruction::Bst,
+            3 => Instruction::Jnz,
+            4 => Instruction::Bxc,
+            5 => Instruction::Out,
+            6 => Instruction::Bdv,
+            7 => Instruction::Cdv,
+            x => panic!("{x} is not a 3-bit opcode"),
+        }
+    }
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Adv => "adv",
+            Instruction::Bxl => "bxl",
+            Instruction::Bst => "bst",
+            Instruction::Jnz => "jnz",
+            Instruction::Bxc => "bxc",
+            Instruction::Out => "out",
+            Instruction::Bdv => "bdv",
+            Instruction::Cdv => "cdv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Computer {
+    a: u64,
+    b: u64,
+    c: u64,
+    program: Vec<u8>,
+}
+
+fn register(s: &str) -> IResult<&str, u64> {
+    delimited(
+        tuple((tag("Register "), alpha1, tag(": "))),
+        unsigned,
+        multispace1,
+    )(s)
 }
+fn program(s: &str) -> IResult<&str, Vec<u8>> {
+    delimited(
+        tag("Program: "),
+        separated_list1(tag(","), opcode),
+        multispace0,
+    )(s)
+}
+
+impl Computer {
+    fn from_input(input: &str) -> Computer {
+        let (_, (a, b, c, program)) =
+            tuple((register, register, register, program))(input).unwrap();
+        Computer { a, b, c, program }
+    }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+    fn combo(&self, operand: u8) -> u64 {
+        match operand {
+            0..=3 => operand as u64,
+            4 => self.a,
+            5 => self.b,
+            6 => self.c,
+            x => panic!("combo operand {x} is reserved"),
+        }
+    }
+
+    /// A shifted right by the combo operand, every bit is shifted out once it reaches 64
+    fn a_shifted(&self, operand: u8) -> u64 {
+        u32::try_from(self.combo(operand))
+            .ok()
+            .and_then(|shift| self.a.checked_shr(shift))
+            .unwrap_or(0)
+    }
+
+    /// runs the program to completion from the current registers collecting everything output
+    fn run(&mut self) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut ip = 0;
+        while ip + 1 < self.program.len() {
+            let operand = self.program[ip + 1];
+            ip += 2;
+            match Instruction::from_opcode(self.program[ip - 2]) {
+                Instruction::Adv => self.a = self.a_shifted(operand),
+                Instruction::Bxl => self.b ^= operand as u64,
+                Instruction::Bst => self.b = self.combo(operand) % 8,
+                Instruction::Jnz if self.a != 0 => ip = operand as usize,
+                Instruction::Jnz => (),
+                Instruction::Bxc => self.b ^= self.c,
+                Instruction::Out => output.push((self.combo(operand) % 8) as u8),
+                Instruction::Bdv => self.b = self.a_shifted(operand),
+                Instruction::Cdv => self.c = self.a_shifted(operand),
+            }
+        }
+        output
+    }
+
+    fn run_with_a(&self, a: u64) -> Vec<u8> {
+        let mut computer = Computer { a, ..self.clone() };
+        computer.run()
+    }
+
+    /// Programs of this shape consume the lowest octal digit of A on every loop and stop once A
+    /// reaches zero, so the last output only depends on the highest digit of A.
+    /// Build A up from its highest digit keeping every candidate whose output matches the
+    /// tail of the program.
+    fn quine_a(&self) -> Option<u64> {
+        (0..self.program.len())
+            .rev()
+            .fold(vec![0u64], |candidates, i| {
+                candidates
+                    .into_iter()
+                    .flat_map(|a| (0..8).map(move |digit| a * 8 + digit))
+                    .filter(|a| self.run_with_a(*a) == self.program[i..])
+                    .collect()
+            })
+            .into_iter()
+            .min()
+    }
+
+    fn disassemble(&self) -> String {
+        self.program
+            .chunks(2)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let instruction = Instruction::from_opcode(chunk[0]);
+                let operand = chunk[1];
+                let combo = match operand {
+                    0..=3 => operand.to_string(),
+                    4 => "A".to_string(),
+                    5 => "B".to_string(),
+                    6 => "C".to_string(),
+                    _ => "?".to_string(),
+                };
+                let description = match instruction {
+                    Instruction::Adv => format!("A = A >> {combo}"),
+                    Instruction::Bxl => format!("B = B ^ {operand}"),
+                    Instruction::Bst => format!("B = {combo} % 8"),
+                    Instruction::Jnz => format!("if A != 0 jump {operand}"),
+                    Instruction::Bxc => "B = B ^ C".to_string(),
+                    Instruction::Out => format!("out {combo} % 8"),
+                    Instruction::Bdv => format!("B = A >> {combo}"),
+                    Instruction::Cdv => format!("C = A >> {combo}"),
+                };
+                format!(
+                    "{:>2}: {} {} | {}",
+                    i * 2,
+                    instruction.mnemonic(),
+                    operand,
+                    description
+                )
+            })
+            .join("\n")
+    }
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let mut computer = Computer::from_input(&input);
+    Ok(computer.run().iter().join(","))
+}
+
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let computer = Computer::from_input(&input);
+    Ok(computer.quine_a().unwrap().to_string())
+}
+
+/// Lists the program one instruction per line with what each one does.
+pub fn debug(input: String, _args: &[String]) -> Result<String, error::Error> {
+    Ok(Computer::from_input(&input).disassemble())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+    const QUINE_INPUT: &str = "Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0";
+
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "4,6,3,5,6,3,5,2,1,0")
+    }
+    #[test]
+    fn test_small_programs() {
+        let mut computer = Computer::from_input(
+            "Register A: 0
+Register B: 0
+Register C: 9
+
+Program: 2,6",
+        );
+        computer.run();
+        assert_eq!(computer.b, 1);
+
+        let mut computer = Computer::from_input(
+            "Register A: 10
+Register B: 0
+Register C: 0
+
+Program: 5,0,5,1,5,4",
+        );
+        assert_eq!(computer.run(), vec![0, 1, 2]);
+
+        let mut computer = Computer::from_input(
+            "Register A: 0
+Register B: 2024
+Register C: 43690
+
+Program: 4,0",
+        );
+        computer.run();
+        assert_eq!(computer.b, 44354);
+    }
+    #[test]
+    fn test_wide_shifts() {
+        let mut computer = Computer::from_input(
+            "Register A: 12345
+Register B: 64
+Register C: 0
+
+Program: 7,5,0,5",
+        );
+        computer.run();
+        assert_eq!((computer.a, computer.c), (0, 0));
+
+        let mut computer = Computer::from_input(
+            "Register A: 12345
+Register B: 0
+Register C: 1099511627776
+
+Program: 6,6",
+        );
+        computer.run();
+        assert_eq!(computer.b, 0);
+    }
+    #[test]
+    fn test_disassemble() {
+        let computer = Computer::from_input(QUINE_INPUT);
+        assert_eq!(
+            computer.disassemble(),
+            " 0: adv 3 | A = A >> 3
+ 2: out 4 | out A % 8
+ 4: jnz 0 | if A != 0 jump 0"
+        )
     }
-    #[ignore]
     #[test]
     fn test_part2() {
-        let input = INPUT.to_string();
+        let input = QUINE_INPUT.to_string();
         let result = part2(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "117440")
     }
 }
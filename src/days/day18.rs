@@ -1,31 +1,131 @@
-use crate::error;
+use std::collections::HashSet;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+use nom::{
+    bytes::complete::tag,
+    character::complete::{line_ending, u32 as unsigned},
+    multi::separated_list1,
+    sequence::separated_pair,
+    IResult,
+};
+use pathfinding::prelude::bfs;
+
+use crate::{
+    error,
+    utils::{ArrayUtils2D, Direction},
+};
+
+const SIZE: usize = 71;
+const FALLEN: usize = 1024;
+
+fn byte(s: &str) -> IResult<&str, (usize, usize)> {
+    let (s, (x, y)) = separated_pair(unsigned, tag(","), unsigned)(s)?;
+    // stored as (row, col) to match the rest of the grid code
+    Ok((s, (y as usize, x as usize)))
+}
+fn bytes(s: &str) -> Vec<(usize, usize)> {
+    separated_list1(line_ending, byte)(s.trim()).ok().unwrap().1
+}
+
+struct MemorySpace {
+    size: usize,
+    limits: ArrayUtils2D,
+    bytes: Vec<(usize, usize)>,
+}
+
+impl MemorySpace {
+    fn from_input(input: &str, size: usize) -> MemorySpace {
+        MemorySpace {
+            size,
+            limits: ArrayUtils2D::new(size, size),
+            bytes: bytes(input),
+        }
+    }
+
+    /// shortest number of steps from the top left to the bottom right once `fallen` bytes
+    /// have landed, None if the exit is cut off
+    fn shortest_path(&self, fallen: usize) -> Option<usize> {
+        let corrupted: HashSet<(usize, usize)> = self.bytes[..fallen].iter().copied().collect();
+        let exit = (self.size - 1, self.size - 1);
+        bfs(
+            &(0, 0),
+            |p| {
+                Direction::ALL
+                    .iter()
+                    .flat_map(|d| self.limits.step(*p, *d))
+                    .filter(|n| !corrupted.contains(n))
+                    .collect::<Vec<(usize, usize)>>()
+            },
+            |p| *p == exit,
+        )
+        .map(|path| path.len() - 1)
+    }
+
+    /// Once the exit is cut off it stays cut off, so binary search for the first
+    /// prefix of bytes that blocks the path.
+    fn first_blocking_byte(&self) -> Option<(usize, usize)> {
+        if self.shortest_path(self.bytes.len()).is_some() {
+            return None;
+        }
+        let (mut open, mut blocked) = (0, self.bytes.len());
+        while blocked - open > 1 {
+            let mid = (open + blocked) / 2;
+            if self.shortest_path(mid).is_some() {
+                open = mid
+            } else {
+                blocked = mid
+            }
+        }
+        Some(self.bytes[blocked - 1])
+    }
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let space = MemorySpace::from_input(&input, SIZE);
+    Ok(space.shortest_path(FALLEN).unwrap().to_string())
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let space = MemorySpace::from_input(&input, SIZE);
+    let (row, col) = space.first_blocking_byte().unwrap();
+    Ok(format!("{col},{row}"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0";
     #[test]
     fn test_part1() {
-        let input = INPUT.to_string();
-        let result = part1(input);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        let space = MemorySpace::from_input(INPUT, 7);
+        assert_eq!(space.shortest_path(12), Some(22))
     }
-    #[ignore]
     #[test]
     fn test_part2() {
-        let input = INPUT.to_string();
-        let result = part2(input);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        let space = MemorySpace::from_input(INPUT, 7);
+        assert_eq!(space.first_blocking_byte(), Some((1, 6)))
     }
 }
@@ -1,31 +1,124 @@
+use std::collections::HashMap;
+
 use crate::error;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<char, usize>,
+    terminal: bool,
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+/// Towel patterns stored as a prefix tree so every pattern starting at a given position in a
+/// design can be found in a single walk down the tree.
+#[derive(Debug)]
+struct Trie {
+    nodes: Vec<TrieNode>,
+}
+
+impl Trie {
+    fn from_patterns<'a>(patterns: impl Iterator<Item = &'a str>) -> Trie {
+        let mut trie = Trie {
+            nodes: vec![TrieNode::default()],
+        };
+        patterns.for_each(|p| trie.insert(p));
+        trie
+    }
+
+    fn insert(&mut self, pattern: &str) {
+        let mut node = 0;
+        for c in pattern.chars() {
+            node = match self.nodes[node].children.get(&c) {
+                Some(next) => *next,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(c, next);
+                    next
+                }
+            };
+        }
+        self.nodes[node].terminal = true;
+    }
+
+    /// lengths of every pattern that is a prefix of `design`
+    fn prefix_lengths(&self, design: &[char]) -> Vec<usize> {
+        let mut lengths = Vec::new();
+        let mut node = 0;
+        for (i, c) in design.iter().enumerate() {
+            match self.nodes[node].children.get(c) {
+                Some(next) => node = *next,
+                None => break,
+            }
+            if self.nodes[node].terminal {
+                lengths.push(i + 1);
+            }
+        }
+        lengths
+    }
+
+    /// number of distinct ways to lay the patterns end to end to form the design
+    fn arrangements(&self, design: &str) -> u64 {
+        let design: Vec<char> = design.chars().collect();
+        // ways[i] is the number of arrangements of design[i..]
+        let mut ways = vec![0u64; design.len() + 1];
+        ways[design.len()] = 1;
+        for i in (0..design.len()).rev() {
+            ways[i] = self
+                .prefix_lengths(&design[i..])
+                .into_iter()
+                .map(|l| ways[i + l])
+                .sum();
+        }
+        ways[0]
+    }
+}
+
+fn parse_input(input: &str) -> (Trie, Vec<&str>) {
+    let (patterns, designs) = input.split_once("\n\n").unwrap();
+    let trie = Trie::from_patterns(patterns.split(',').map(str::trim));
+    (trie, designs.lines().filter(|l| !l.is_empty()).collect())
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let (trie, designs) = parse_input(&input);
+    let result = designs
+        .into_iter()
+        .filter(|d| trie.arrangements(d) > 0)
+        .count();
+    Ok(result.to_string())
+}
+
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let (trie, designs) = parse_input(&input);
+    let result: u64 = designs.into_iter().map(|d| trie.arrangements(d)).sum();
+    Ok(result.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb";
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "6")
     }
-    #[ignore]
     #[test]
     fn test_part2() {
         let input = INPUT.to_string();
         let result = part2(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "16")
     }
 }
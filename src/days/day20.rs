@@ -1,31 +1,128 @@
-use crate::error;
+use std::collections::VecDeque;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+use array2d::Array2D;
+
+use crate::{
+    error,
+    utils::{find_in_grid, grid_from_input, ArrayUtils2D, Direction},
+};
+
+const THRESHOLD: usize = 100;
+
+struct Racetrack {
+    start: (usize, usize),
+    limits: ArrayUtils2D,
+    from_start: Array2D<Option<usize>>,
+    to_end: Array2D<Option<usize>>,
+}
+
+impl Racetrack {
+    fn from_input(input: &str) -> Racetrack {
+        let grid = grid_from_input(input);
+        let limits = ArrayUtils2D::from_array(&grid);
+        let start = find_in_grid(&grid, 'S').unwrap();
+        let end = find_in_grid(&grid, 'E').unwrap();
+        let from_start = Racetrack::distances(&grid, &limits, start);
+        let to_end = Racetrack::distances(&grid, &limits, end);
+        Racetrack {
+            start,
+            limits,
+            from_start,
+            to_end,
+        }
+    }
+
+    /// breadth first distance to every track cell reachable from `source`
+    fn distances(
+        grid: &Array2D<char>,
+        limits: &ArrayUtils2D,
+        source: (usize, usize),
+    ) -> Array2D<Option<usize>> {
+        let mut dist = Array2D::filled_with(None, grid.num_rows(), grid.num_columns());
+        dist[source] = Some(0);
+        let mut discover = VecDeque::from([source]);
+        while let Some(p) = discover.pop_front() {
+            let d = dist[p].unwrap();
+            for n in Direction::ALL.iter().flat_map(|dir| limits.step(p, *dir)) {
+                if grid[n] != '#' && dist[n].is_none() {
+                    dist[n] = Some(d + 1);
+                    discover.push_back(n);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Number of cheats that skip through walls for at most `radius` picoseconds and save at
+    /// least `threshold` picoseconds over the best honest time.
+    /// A cheat is identified by where it starts and ends, so every pair of track cells within
+    /// manhattan distance `radius` of each other is one candidate cheat.
+    fn cheats(&self, radius: usize, threshold: usize) -> usize {
+        let best = self.to_end[self.start].unwrap();
+        let r = radius as isize;
+        self.limits
+            .all_points()
+            .into_iter()
+            .filter_map(|p| self.from_start[p].map(|d| (p, d)))
+            .map(|((i, j), before)| {
+                (-r..=r)
+                    .flat_map(|di| {
+                        let span = r - di.abs();
+                        (-span..=span).map(move |dj| (di, dj))
+                    })
+                    .filter_map(|(di, dj)| {
+                        let ni = i.checked_add_signed(di)?;
+                        let nj = j.checked_add_signed(dj)?;
+                        let after = (*self.to_end.get(ni, nj)?)?;
+                        Some(before + di.unsigned_abs() + dj.unsigned_abs() + after)
+                    })
+                    .filter(|time| time + threshold <= best)
+                    .count()
+            })
+            .sum()
+    }
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let track = Racetrack::from_input(&input);
+    Ok(track.cheats(2, THRESHOLD).to_string())
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let track = Racetrack::from_input(&input);
+    Ok(track.cheats(20, THRESHOLD).to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
     #[test]
     fn test_part1() {
-        let input = INPUT.to_string();
-        let result = part1(input);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        let track = Racetrack::from_input(INPUT);
+        assert_eq!(track.cheats(2, 64), 1);
+        assert_eq!(track.cheats(2, 20), 5);
+        assert_eq!(track.cheats(2, 2), 44);
     }
-    #[ignore]
     #[test]
     fn test_part2() {
-        let input = INPUT.to_string();
-        let result = part2(input);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        let track = Racetrack::from_input(INPUT);
+        assert_eq!(track.cheats(20, 76), 3);
+        assert_eq!(track.cheats(20, 74), 7);
+        assert_eq!(track.cheats(20, 50), 285);
     }
 }
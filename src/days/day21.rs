@@ -1,31 +1,202 @@
+use itertools::Itertools;
+use memoize::memoize;
+
 use crate::error;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+const PART1_ROBOTS: usize = 2;
+const PART2_ROBOTS: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Keypad {
+    // +---+---+---+
+    // | 7 | 8 | 9 |
+    // +---+---+---+
+    // | 4 | 5 | 6 |
+    // +---+---+---+
+    // | 1 | 2 | 3 |
+    // +---+---+---+
+    //     | 0 | A |
+    //     +---+---+
+    Numeric,
+    //     +---+---+
+    //     | ^ | A |
+    // +---+---+---+
+    // | < | v | > |
+    // +---+---+---+
+    Directional,
+}
+
+impl Keypad {
+    fn position(&self, key: char) -> (i32, i32) {
+        match (self, key) {
+            (Keypad::Numeric, '7') => (0, 0),
+            (Keypad::Numeric, '8') => (0, 1),
+            (Keypad::Numeric, '9') => (0, 2),
+            (Keypad::Numeric, '4') => (1, 0),
+            (Keypad::Numeric, '5') => (1, 1),
+            (Keypad::Numeric, '6') => (1, 2),
+            (Keypad::Numeric, '1') => (2, 0),
+            (Keypad::Numeric, '2') => (2, 1),
+            (Keypad::Numeric, '3') => (2, 2),
+            (Keypad::Numeric, '0') => (3, 1),
+            (Keypad::Numeric, 'A') => (3, 2),
+            (Keypad::Directional, '^') => (0, 1),
+            (Keypad::Directional, 'A') => (0, 2),
+            (Keypad::Directional, '<') => (1, 0),
+            (Keypad::Directional, 'v') => (1, 1),
+            (Keypad::Directional, '>') => (1, 2),
+            (pad, x) => panic!("{x} is not on the {pad:?} keypad"),
+        }
+    }
+    fn gap(&self) -> (i32, i32) {
+        match self {
+            Keypad::Numeric => (3, 0),
+            Keypad::Directional => (0, 0),
+        }
+    }
+
+    /// The move sequences worth considering to go from one key to another and press it.
+    /// Zig-zagging is never better than doing all of one direction then all of the other, so
+    /// there are at most two candidates, less any that would pass over the gap.
+    fn moves(&self, from: char, to: char) -> Vec<String> {
+        let (r1, c1) = self.position(from);
+        let (r2, c2) = self.position(to);
+        let vertical = if r2 > r1 { "v" } else { "^" }.repeat(r1.abs_diff(r2) as usize);
+        let horizontal = if c2 > c1 { ">" } else { "<" }.repeat(c1.abs_diff(c2) as usize);
+        let mut candidates = Vec::new();
+        if (r1, c2) != self.gap() {
+            candidates.push(format!("{horizontal}{vertical}A"));
+        }
+        if (r2, c1) != self.gap() {
+            candidates.push(format!("{vertical}{horizontal}A"));
+        }
+        candidates.into_iter().unique().collect()
+    }
+}
+
+/// presses needed by the human to make the robot at `depth` move from `from` to `to` on its
+/// directional keypad and press it
+#[memoize]
+fn press_cost(from: char, to: char, depth: usize) -> u64 {
+    if depth == 0 {
+        1
+    } else {
+        Keypad::Directional
+            .moves(from, to)
+            .iter()
+            .map(|m| sequence_cost(m, depth - 1))
+            .min()
+            .unwrap()
+    }
+}
+
+/// presses needed by the human to type `sequence` on a directional keypad `depth` robots away
+fn sequence_cost(sequence: &str, depth: usize) -> u64 {
+    "A".chars()
+        .chain(sequence.chars())
+        .tuple_windows()
+        .map(|(from, to)| press_cost(from, to, depth))
+        .sum()
+}
+
+/// Picks the cheapest candidate for each key in `sequence` given the number of directional
+/// keypads still to go.
+fn best_moves(pad: Keypad, sequence: &str, depth: usize) -> String {
+    "A".chars()
+        .chain(sequence.chars())
+        .tuple_windows()
+        .map(|(from, to)| {
+            pad.moves(from, to)
+                .into_iter()
+                .min_by_key(|m| sequence_cost(m, depth))
+                .unwrap()
+        })
+        .collect()
+}
+
+/// fewest presses the human needs to type `code` on the numeric keypad through `robots`
+/// directional keypads
+fn code_cost(code: &str, robots: usize) -> u64 {
+    sequence_cost(&best_moves(Keypad::Numeric, code, robots), robots)
+}
+
+/// An optimal sequence of presses on every keypad in the chain, starting at the keypad that
+/// drives the numeric keypad and ending with what the human types.
+/// Every layer is materialised so only use this for small numbers of robots.
+fn layers(code: &str, robots: usize) -> Vec<String> {
+    let mut layers = vec![best_moves(Keypad::Numeric, code, robots)];
+    for depth in (0..robots).rev() {
+        let next = best_moves(Keypad::Directional, layers.last().unwrap(), depth);
+        layers.push(next);
+    }
+    layers
+}
+
+fn complexity(input: &str, robots: usize) -> u64 {
+    input
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|code| {
+            let numeric: u64 = code.trim_end_matches('A').parse().unwrap();
+            numeric * code_cost(code, robots)
+        })
+        .sum()
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    Ok(complexity(&input, PART1_ROBOTS).to_string())
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+pub fn part2(input: String) -> Result<String, error::Error> {
+    Ok(complexity(&input, PART2_ROBOTS).to_string())
+}
+
+/// Lists the presses on every keypad for each code through the given number of robots, 2 by
+/// default. Every layer is a few times longer than the one before so keep the number small.
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let robots = match args.first() {
+        Some(r) => r
+            .parse::<usize>()
+            .map_err(|_| error::Error::InvalidArgument(r.to_string()))?,
+        None => PART1_ROBOTS,
+    };
+    Ok(input
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|code| format!("{code}: {}", layers(code, robots).join(" <- ")))
+        .collect::<Vec<String>>()
+        .join("\n"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "029A
+980A
+179A
+456A
+379A";
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "126384")
+    }
+    #[test]
+    fn test_layers() {
+        let layers = layers("029A", 2);
+        assert_eq!(layers.len(), 3);
+        assert_eq!(layers[0].len(), "<A^A>^^AvvvA".len());
+        assert_eq!(layers[1].len(), "v<<A>>^A<A>AvA<^AA>A<vAAA>^A".len());
+        assert_eq!(layers[2].len(), 68);
+        assert_eq!(layers[2].len() as u64, code_cost("029A", 2));
     }
-    #[ignore]
     #[test]
     fn test_part2() {
         let input = INPUT.to_string();
         let result = part2(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "154115708116294")
     }
 }
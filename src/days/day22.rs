@@ -1,31 +1,134 @@
+use std::thread;
+
 use crate::error;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+const STEPS: usize = 2000;
+const PRUNE: u64 = 16777216;
+/// price changes range from -9 to 9 so a window of four fits in a base 19 number
+const WINDOWS: usize = 19 * 19 * 19 * 19;
+
+fn evolve(secret: u64) -> u64 {
+    let secret = ((secret << 6) ^ secret) % PRUNE;
+    let secret = ((secret >> 5) ^ secret) % PRUNE;
+    ((secret << 11) ^ secret) % PRUNE
+}
+
+/// every secret number a buyer generates after the initial one
+struct Secrets {
+    secret: u64,
+}
+
+impl Iterator for Secrets {
+    type Item = u64;
+    fn next(&mut self) -> Option<u64> {
+        self.secret = evolve(self.secret);
+        Some(self.secret)
+    }
+}
+
+fn secrets(initial: u64) -> Secrets {
+    Secrets { secret: initial }
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+fn parse_input(input: &str) -> Vec<u64> {
+    input.lines().flat_map(|l| l.trim().parse()).collect()
+}
+
+/// Total bananas across `buyers` for every window of four price changes, indexed by the base 19
+/// encoding of the window. Only the first time a buyer sees a window counts.
+fn window_totals(buyers: &[u64]) -> Vec<u32> {
+    let mut totals = vec![0u32; WINDOWS];
+    // the last buyer to have seen each window, offset by one so zero means never seen
+    let mut seen_by = vec![0usize; WINDOWS];
+    for (buyer, initial) in buyers.iter().enumerate() {
+        let mut price = (initial % 10) as i64;
+        let mut window = 0usize;
+        for (i, secret) in secrets(*initial).take(STEPS).enumerate() {
+            let next_price = (secret % 10) as i64;
+            window = (window * 19 + (next_price - price + 9) as usize) % WINDOWS;
+            price = next_price;
+            if i >= 3 && seen_by[window] != buyer + 1 {
+                seen_by[window] = buyer + 1;
+                totals[window] += price as u32;
+            }
+        }
+    }
+    totals
+}
+
+/// the most bananas any single sequence of four price changes can buy
+fn most_bananas(buyers: &[u64], parallel: bool) -> u32 {
+    let totals = if parallel {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = buyers.len().div_ceil(threads).max(1);
+        thread::scope(|s| {
+            buyers
+                .chunks(chunk_size)
+                .map(|chunk| s.spawn(move || window_totals(chunk)))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .reduce(|mut acc, totals| {
+                    acc.iter_mut().zip(totals).for_each(|(a, t)| *a += t);
+                    acc
+                })
+                .unwrap_or_default()
+        })
+    } else {
+        window_totals(buyers)
+    };
+    totals.into_iter().max().unwrap_or(0)
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let result: u64 = parse_input(&input)
+        .into_iter()
+        .map(|s| secrets(s).nth(STEPS - 1).unwrap())
+        .sum();
+    Ok(result.to_string())
+}
+
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let buyers = parse_input(&input);
+    Ok(most_bananas(&buyers, true).to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "1
+10
+100
+2024";
+    const SECOND_INPUT: &str = "1
+2
+3
+2024";
+    #[test]
+    fn test_secrets() {
+        let expected = vec![
+            15887950, 16495136, 527345, 704524, 1553684, 12683156, 11100544, 12249484, 7753432,
+            5908254,
+        ];
+        assert_eq!(secrets(123).take(10).collect::<Vec<u64>>(), expected)
+    }
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "37327623")
     }
-    #[ignore]
     #[test]
     fn test_part2() {
-        let input = INPUT.to_string();
+        let input = SECOND_INPUT.to_string();
         let result = part2(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "23")
+    }
+    #[test]
+    fn test_serial_matches_parallel() {
+        let buyers = parse_input(SECOND_INPUT);
+        assert_eq!(most_bananas(&buyers, false), most_bananas(&buyers, true))
     }
 }
@@ -1,31 +1,79 @@
-use crate::error;
+use itertools::Itertools;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+use crate::{error, graph::Graph};
+
+fn parse_input(input: &str) -> Graph {
+    Graph::from_edges(input.lines().flat_map(|l| l.trim().split_once('-')))
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let graph = parse_input(&input);
+    let result = graph
+        .triangles()
+        .into_iter()
+        .filter(|t| t.iter().any(|id| graph.name(*id).starts_with('t')))
+        .count();
+    Ok(result.to_string())
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let graph = parse_input(&input);
+    let password = graph
+        .maximum_clique()
+        .into_iter()
+        .map(|id| graph.name(id))
+        .sorted()
+        .join(",");
+    Ok(password)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "7")
     }
-    #[ignore]
     #[test]
     fn test_part2() {
         let input = INPUT.to_string();
         let result = part2(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "co,de,ka,ta")
     }
 }
@@ -1,31 +1,380 @@
+use std::{collections::HashMap, fs, io};
+
+use itertools::Itertools;
+
 use crate::error;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+const SWAPPED_PAIRS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Operation {
+    And,
+    Or,
+    Xor,
+}
+
+impl Operation {
+    fn from_str(s: &str) -> Operation {
+        match s {
+            "AND" => Operation::And,
+            "OR" => Operation::Or,
+            "XOR" => Operation::Xor,
+            x => panic!("unexpected gate {x}"),
+        }
+    }
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Operation::And => a & b,
+            Operation::Or => a | b,
+            Operation::Xor => a ^ b,
+        }
+    }
+    fn label(&self) -> &'static str {
+        match self {
+            Operation::And => "AND",
+            Operation::Or => "OR",
+            Operation::Xor => "XOR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Gate {
+    inputs: (String, String),
+    operation: Operation,
+    output: String,
 }
 
-pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+impl Gate {
+    fn from_line(line: &str) -> Gate {
+        let (a, op, b, _, output) = line.split_whitespace().collect_tuple().unwrap();
+        Gate {
+            inputs: (a.to_string(), b.to_string()),
+            operation: Operation::from_str(op),
+            output: output.to_string(),
+        }
+    }
+    fn has_input(&self, wire: &str) -> bool {
+        self.inputs.0 == wire || self.inputs.1 == wire
+    }
+    /// true when the gate reads straight from the x and y input bits
+    fn is_first_layer(&self) -> bool {
+        [&self.inputs.0, &self.inputs.1]
+            .iter()
+            .all(|w| w.starts_with('x') || w.starts_with('y'))
+    }
+}
+
+struct Circuit {
+    initial: HashMap<String, bool>,
+    gates: Vec<Gate>,
+}
+
+impl Circuit {
+    fn from_input(input: &str) -> Circuit {
+        let (wires, gates) = input.split_once("\n\n").unwrap();
+        let initial = wires
+            .lines()
+            .map(|l| {
+                let (wire, value) = l.split_once(": ").unwrap();
+                (wire.to_string(), value.trim() == "1")
+            })
+            .collect();
+        let gates = gates
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(Gate::from_line)
+            .collect();
+        Circuit { initial, gates }
+    }
+
+    /// Evaluates the gates in topological order, a gate is ready once both of its inputs
+    /// have a value. Returns None if the circuit has a cycle.
+    fn evaluate(&self) -> Option<HashMap<String, bool>> {
+        let mut values = self.initial.clone();
+        let mut remaining: Vec<&Gate> = self.gates.iter().collect();
+        while !remaining.is_empty() {
+            let (ready, waiting): (Vec<&Gate>, Vec<&Gate>) = remaining.into_iter().partition(|g| {
+                values.contains_key(&g.inputs.0) && values.contains_key(&g.inputs.1)
+            });
+            if ready.is_empty() {
+                return None;
+            }
+            for g in ready {
+                let value = g.operation.apply(values[&g.inputs.0], values[&g.inputs.1]);
+                values.insert(g.output.clone(), value);
+            }
+            remaining = waiting;
+        }
+        Some(values)
+    }
+
+    fn number(values: &HashMap<String, bool>, prefix: char) -> u64 {
+        values
+            .iter()
+            .filter(|(w, _)| w.starts_with(prefix))
+            .sorted()
+            .rev()
+            .fold(0, |acc, (_, v)| (acc << 1) | *v as u64)
+    }
+
+    fn highest_z(&self) -> String {
+        self.gates
+            .iter()
+            .map(|g| &g.output)
+            .filter(|w| w.starts_with('z'))
+            .max()
+            .unwrap()
+            .clone()
+    }
+
+    /// A ripple carry adder is built from the same five gates for every bit
+    ///   x ^ y -> s      x & y -> c1
+    ///   s ^ carry -> z  s & carry -> c2
+    ///   c1 | c2 -> carry out
+    /// apart from bit 0 which is a half adder, and the top z which is the final carry.
+    /// Any gate whose output breaks the shape of that structure must be one of the swapped wires.
+    fn miswired_outputs(&self) -> Vec<String> {
+        let highest_z = self.highest_z();
+        self.gates
+            .iter()
+            .filter(|g| {
+                let feeds = |op: Operation| {
+                    self.gates
+                        .iter()
+                        .any(|other| other.operation == op && other.has_input(&g.output))
+                };
+                let first_bit = g.has_input("x00");
+                match g.operation {
+                    // checked before the z rules as an x ^ y gate can't output z past bit 0
+                    Operation::Xor if g.is_first_layer() && !first_bit => {
+                        g.output.starts_with('z') || !feeds(Operation::Xor)
+                    }
+                    _ if g.output.starts_with('z') && g.output != highest_z => {
+                        g.operation != Operation::Xor
+                    }
+                    Operation::Or if g.output == highest_z => false,
+                    _ if g.output == highest_z => true,
+                    Operation::Xor => !g.output.starts_with('z'),
+                    Operation::And if !first_bit => !feeds(Operation::Or),
+                    _ => false,
+                }
+            })
+            .map(|g| g.output.clone())
+            .sorted()
+            .collect()
+    }
+
+    fn to_dot(&self) -> String {
+        let mut lines = vec!["digraph circuit {".to_string()];
+        for (i, g) in self.gates.iter().enumerate() {
+            lines.push(format!(
+                "    gate{i} [label=\"{}\" shape=box];",
+                g.operation.label()
+            ));
+            lines.push(format!("    {} -> gate{i};", g.inputs.0));
+            lines.push(format!("    {} -> gate{i};", g.inputs.1));
+            lines.push(format!("    gate{i} -> {};", g.output));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    fn write_dot(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_dot())
+    }
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let circuit = Circuit::from_input(&input);
+    let values = circuit.evaluate().unwrap();
+    Ok(Circuit::number(&values, 'z').to_string())
+}
+
+pub fn part2(input: String) -> Result<String, error::Error> {
+    let circuit = Circuit::from_input(&input);
+    let miswired = circuit.miswired_outputs();
+    if miswired.len() != SWAPPED_PAIRS * 2 {
+        return Err(error::Error::InvalidInput(format!(
+            "expected {} miswired outputs but found {}",
+            SWAPPED_PAIRS * 2,
+            miswired.join(",")
+        )));
+    }
+    Ok(miswired.join(","))
+}
+
+/// Draws the circuit in the graphviz dot format, written to the given path if there is one,
+/// e.g. `cargo run 24 debug data/day24.dot` then `dot -Tsvg data/day24.dot`.
+pub fn debug(input: String, args: &[String]) -> Result<String, error::Error> {
+    let circuit = Circuit::from_input(&input);
+    match args.first() {
+        Some(path) => {
+            circuit
+                .write_dot(path)
+                .map_err(error::Error::FileWriteFailed)?;
+            Ok(format!("wrote {path}"))
+        }
+        None => Ok(circuit.to_dot()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02";
+    const LARGER_INPUT: &str = "x00: 1
+x01: 0
+x02: 1
+x03: 1
+x04: 0
+y00: 1
+y01: 1
+y02: 1
+y03: 1
+y04: 1
+
+ntg XOR fgs -> mjb
+y02 OR x01 -> tnw
+kwq OR kpj -> z05
+x00 OR x03 -> fst
+tgd XOR rvg -> z01
+vdt OR tnw -> bfw
+bfw AND frj -> z10
+ffh OR nrd -> bqk
+y00 AND y03 -> djm
+y03 OR y00 -> psh
+bqk OR frj -> z08
+tnw OR fst -> frj
+gnj AND tgd -> z11
+bfw XOR mjb -> z00
+x03 OR x00 -> vdt
+gnj AND wpb -> z02
+x04 AND y00 -> kjc
+djm OR pbm -> qhw
+nrd AND vdt -> hwm
+kjc AND fst -> rvg
+y04 OR y02 -> fgs
+y01 AND x02 -> pbm
+ntg OR kjc -> kwq
+psh XOR fgs -> tgd
+qhw XOR tgd -> z09
+pbm OR djm -> kpj
+x03 XOR y03 -> ffh
+x00 XOR y04 -> ntg
+bfw OR bqk -> z06
+nrd XOR fgs -> wpb
+frj XOR qhw -> z04
+bqk OR frj -> z07
+y03 OR x01 -> nrd
+hwm AND bqk -> z03
+tgd XOR rvg -> z12
+tnw OR pbm -> gnj";
+
+    /// a correct `bits` wide ripple carry adder with the outputs of the named wires swapped
+    fn adder(bits: usize, swaps: &[(&str, &str)]) -> String {
+        let mut gates = vec![
+            (
+                "x00".to_string(),
+                "XOR",
+                "y00".to_string(),
+                "z00".to_string(),
+            ),
+            (
+                "x00".to_string(),
+                "AND",
+                "y00".to_string(),
+                "car00".to_string(),
+            ),
+        ];
+        for i in 1..bits {
+            let carry_in = format!("car{:02}", i - 1);
+            let carry_out = if i == bits - 1 {
+                format!("z{bits:02}")
+            } else {
+                format!("car{i:02}")
+            };
+            let (x, y) = (format!("x{i:02}"), format!("y{i:02}"));
+            gates.extend([
+                (x.clone(), "XOR", y.clone(), format!("sum{i:02}")),
+                (x, "AND", y, format!("and{i:02}")),
+                (
+                    format!("sum{i:02}"),
+                    "XOR",
+                    carry_in.clone(),
+                    format!("z{i:02}"),
+                ),
+                (format!("sum{i:02}"), "AND", carry_in, format!("cry{i:02}")),
+                (format!("and{i:02}"), "OR", format!("cry{i:02}"), carry_out),
+            ]);
+        }
+        let swapped = |w: String| {
+            swaps
+                .iter()
+                .find_map(|(a, b)| match w.as_str() {
+                    x if x == *a => Some(b.to_string()),
+                    x if x == *b => Some(a.to_string()),
+                    _ => None,
+                })
+                .unwrap_or(w)
+        };
+        let mut wires = (0..bits).flat_map(|i| [format!("x{i:02}: 1"), format!("y{i:02}: 1")]);
+        let mut gates = gates
+            .into_iter()
+            .map(|(a, op, b, out)| format!("{a} {op} {b} -> {}", swapped(out)));
+        format!("{}\n\n{}", wires.join("\n"), gates.join("\n"))
+    }
+
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "4")
     }
-    #[ignore]
     #[test]
-    fn test_part2() {
-        let input = INPUT.to_string();
-        let result = part2(input);
+    fn test_part1_larger() {
+        let input = LARGER_INPUT.to_string();
+        let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "2024")
+    }
+    #[test]
+    fn test_adder_adds() {
+        let circuit = Circuit::from_input(&adder(6, &[]));
+        let values = circuit.evaluate().unwrap();
+        assert_eq!(Circuit::number(&values, 'z'), 63 + 63);
+        assert!(circuit.miswired_outputs().is_empty());
+    }
+    #[test]
+    fn test_miswired_outputs() {
+        let circuit = Circuit::from_input(&adder(6, &[("z02", "cry02"), ("sum04", "and04")]));
+        assert_eq!(
+            circuit.miswired_outputs(),
+            vec!["and04", "cry02", "sum04", "z02"]
+        );
+    }
+    #[test]
+    fn test_first_layer_xor_to_z() {
+        let circuit = Circuit::from_input(&adder(6, &[("z02", "sum04")]));
+        assert_eq!(circuit.miswired_outputs(), vec!["sum04", "z02"]);
+        assert!(part2(adder(6, &[("z02", "sum04")])).is_err());
+    }
+    #[test]
+    fn test_to_dot() {
+        let circuit = Circuit::from_input(INPUT);
+        let dot = circuit.to_dot();
+        assert!(dot.starts_with("digraph circuit {"));
+        assert!(dot.contains("    x01 -> gate1;"));
+        assert!(dot.contains("    gate2 -> z02;"));
     }
 }
@@ -1,31 +1,117 @@
-use crate::error;
+use itertools::Itertools;
 
-pub fn part1(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+use crate::{answers, error};
+
+const PIN_SPACE: usize = 5;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Schematic {
+    Lock([usize; 5]),
+    Key([usize; 5]),
+}
+
+impl Schematic {
+    /// locks have the top row filled and keys the bottom row, the height of each column
+    /// does not count that row
+    fn from_block(block: &str) -> Schematic {
+        let rows = block.lines().map(|l| l.as_bytes()).collect_vec();
+        let mut heights = [0; 5];
+        for (col, height) in heights.iter_mut().enumerate() {
+            *height = rows.iter().filter(|r| r[col] == b'#').count() - 1;
+        }
+        if rows[0].iter().all(|c| *c == b'#') {
+            Schematic::Lock(heights)
+        } else {
+            Schematic::Key(heights)
+        }
+    }
+}
+
+fn parse_input(input: &str) -> (Vec<[usize; 5]>, Vec<[usize; 5]>) {
+    input
+        .split("\n\n")
+        .filter(|b| !b.trim().is_empty())
+        .map(Schematic::from_block)
+        .partition_map(|s| match s {
+            Schematic::Lock(h) => itertools::Either::Left(h),
+            Schematic::Key(h) => itertools::Either::Right(h),
+        })
+}
+
+pub fn part1(input: String) -> Result<String, error::Error> {
+    let (locks, keys) = parse_input(&input);
+    let result = locks
+        .iter()
+        .cartesian_product(keys.iter())
+        .filter(|(lock, key)| lock.iter().zip(key.iter()).all(|(l, k)| l + k <= PIN_SPACE))
+        .count();
+    Ok(result.to_string())
 }
 
+/// there is no puzzle for the last star, instead report which stars have been collected
 pub fn part2(_input: String) -> Result<String, error::Error> {
-    Err(error::Error::SolutionNotExists)
+    Ok(answers::summary())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "";
-    #[ignore]
+    const INPUT: &str = "#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####";
     #[test]
     fn test_part1() {
         let input = INPUT.to_string();
         let result = part1(input);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap(), "3")
+    }
+    #[test]
+    fn test_schematic() {
+        let (locks, keys) = parse_input(INPUT);
+        assert_eq!(locks, vec![[0, 5, 3, 4, 3], [1, 2, 0, 5, 3]]);
+        assert_eq!(keys[0], [5, 0, 2, 1, 3]);
     }
-    #[ignore]
     #[test]
     fn test_part2() {
-        let input = INPUT.to_string();
-        let result = part2(input);
+        let result = part2(INPUT.to_string());
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "")
+        assert_eq!(result.unwrap().lines().count(), 26)
     }
 }
@@ -6,14 +6,24 @@ use std::process::Termination;
 #[derive(Debug)]
 pub enum Error {
     FileNotFound(io::Error),
+    FileWriteFailed(io::Error),
+    StdinReadFailed(io::Error),
     SolutionNotExists,
+    DebugNotExists,
+    InvalidArgument(String),
+    InvalidInput(String),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self {
             Error::FileNotFound(e) => write!(f, "File read failed with: {e}"),
+            Error::FileWriteFailed(e) => write!(f, "File write failed with: {e}"),
+            Error::StdinReadFailed(e) => write!(f, "Stdin read failed with: {e}"),
             Error::SolutionNotExists => write!(f, "Solution not yet implemented."),
+            Error::DebugNotExists => write!(f, "No debugging tools for this day."),
+            Error::InvalidArgument(a) => write!(f, "Invalid argument: {a}"),
+            Error::InvalidInput(i) => write!(f, "Invalid input: {i}"),
         }
     }
 }
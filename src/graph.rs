@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+
+/// An undirected graph whose nodes are named but stored by compact ids handed out in the order
+/// the names are first seen.
+#[derive(Debug, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    adjacency: Vec<HashSet<usize>>,
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph::default()
+    }
+
+    pub fn from_edges<'a>(edges: impl Iterator<Item = (&'a str, &'a str)>) -> Graph {
+        let mut graph = Graph::new();
+        for (a, b) in edges {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+
+    pub fn id(&mut self, name: &str) -> usize {
+        match self.ids.get(name) {
+            Some(id) => *id,
+            None => {
+                let id = self.names.len();
+                self.names.push(name.to_string());
+                self.ids.insert(name.to_string(), id);
+                self.adjacency.push(HashSet::new());
+                id
+            }
+        }
+    }
+
+    pub fn add_edge(&mut self, a: &str, b: &str) {
+        let (a, b) = (self.id(a), self.id(b));
+        self.adjacency[a].insert(b);
+        self.adjacency[b].insert(a);
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn neighbors(&self, id: usize) -> &HashSet<usize> {
+        &self.adjacency[id]
+    }
+
+    /// every set of three mutually connected nodes, each listed once in ascending id order
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let mut triangles = Vec::new();
+        for a in 0..self.len() {
+            for b in self.adjacency[a].iter().filter(|b| **b > a) {
+                for c in self.adjacency[*b].iter().filter(|c| **c > *b) {
+                    if self.adjacency[a].contains(c) {
+                        triangles.push([a, *b, *c]);
+                    }
+                }
+            }
+        }
+        triangles
+    }
+
+    /// every maximal clique, found with Bron–Kerbosch using the highest degree pivot
+    pub fn maximal_cliques(&self) -> Vec<Vec<usize>> {
+        let mut cliques = Vec::new();
+        self.bron_kerbosch(
+            Vec::new(),
+            (0..self.len()).collect(),
+            HashSet::new(),
+            &mut cliques,
+        );
+        cliques
+    }
+
+    pub fn maximum_clique(&self) -> Vec<usize> {
+        self.maximal_cliques()
+            .into_iter()
+            .max_by_key(|c| c.len())
+            .unwrap_or_default()
+    }
+
+    fn bron_kerbosch(
+        &self,
+        clique: Vec<usize>,
+        mut candidates: HashSet<usize>,
+        mut excluded: HashSet<usize>,
+        cliques: &mut Vec<Vec<usize>>,
+    ) {
+        if candidates.is_empty() && excluded.is_empty() {
+            cliques.push(clique);
+            return;
+        }
+        let pivot = *candidates
+            .union(&excluded)
+            .max_by_key(|v| self.adjacency[**v].len())
+            .unwrap();
+        let to_visit: Vec<usize> = candidates
+            .difference(&self.adjacency[pivot])
+            .copied()
+            .collect();
+        for v in to_visit {
+            let mut next_clique = clique.clone();
+            next_clique.push(v);
+            self.bron_kerbosch(
+                next_clique,
+                candidates
+                    .intersection(&self.adjacency[v])
+                    .copied()
+                    .collect(),
+                excluded.intersection(&self.adjacency[v]).copied().collect(),
+                cliques,
+            );
+            candidates.remove(&v);
+            excluded.insert(v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_with_diagonal() -> Graph {
+        Graph::from_edges([("a", "b"), ("b", "c"), ("c", "d"), ("d", "a"), ("a", "c")].into_iter())
+    }
+
+    #[test]
+    fn compact_ids_test() {
+        let graph = square_with_diagonal();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.name(0), "a");
+        assert_eq!(graph.name(3), "d");
+    }
+    #[test]
+    fn triangles_test() {
+        let graph = square_with_diagonal();
+        assert_eq!(graph.triangles().len(), 2);
+    }
+    #[test]
+    fn maximal_cliques_test() {
+        let mut graph = square_with_diagonal();
+        graph.add_edge("b", "d");
+        assert_eq!(graph.maximal_cliques().len(), 1);
+        assert_eq!(graph.maximum_clique().len(), 4);
+    }
+}
@@ -1,4 +1,7 @@
+pub mod answers;
 pub mod days;
 pub mod error;
+pub mod graph;
 pub mod module_runner;
 pub mod utils;
+pub mod word_search;
@@ -1,4 +1,4 @@
-use aoc_2024::module_runner::SolutionRunner;
+use aoc_2024::module_runner::{DebugRunner, SolutionRunner};
 use std::env;
 
 fn help() {
@@ -7,20 +7,35 @@ fn help() {
 day {{1..25}} <integer>
     The day to solve
 part {{1|2}} <integer>
-    The part to solve"
+    The part to solve
+
+day {{1..25}} <integer> debug [args]
+    Run the debugging tools for the day with any extra arguments"
     );
 }
 
+/// the day as a number if it's one of the 25 days of advent
+fn parse_day(day: &str) -> Option<u8> {
+    day.parse().ok().filter(|x| (1..=25).contains(x))
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     match &args[..] {
+        [_, day, mode, rest @ ..] if mode == "debug" => {
+            let Some(day) = parse_day(day) else {
+                help();
+                return;
+            };
+            match DebugRunner::new(day, rest.to_vec()).debug() {
+                Ok(x) => println!("{x}"),
+                Err(e) => eprint!("{e}"),
+            }
+        }
         [_, day, part, ..] => {
-            let day: u8 = match day.parse() {
-                Ok(x) if 1 <= x && x <= 25 => x,
-                _ => {
-                    help();
-                    return;
-                }
+            let Some(day) = parse_day(day) else {
+                help();
+                return;
             };
             let part: u8 = match part.parse() {
                 Ok(x) if x == 1 || x == 2 => x,
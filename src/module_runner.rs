@@ -7,15 +7,19 @@ pub struct SolutionRunner {
     part: u8,
 }
 
+fn read_input(day: u8) -> Result<String, error::Error> {
+    let filename = match day {
+        x if x < 10 => format!("day0{}.txt", x),
+        x => format!("day{}.txt", x),
+    };
+    let crate_root = env!("CARGO_MANIFEST_DIR");
+    let data_dir = format!("{}/data/{}", &crate_root, &filename);
+    fs::read_to_string(data_dir).map_err(error::Error::FileNotFound)
+}
+
 impl SolutionRunner {
     pub fn solve<'a>(&self) -> Result<String, error::Error> {
-        let filename = match self.day {
-            x if x < 10 => format!("day0{}.txt", x),
-            x => format!("day{}.txt", x),
-        };
-        let crate_root = env!("CARGO_MANIFEST_DIR");
-        let data_dir = format!("{}/data/{}", &crate_root, &filename);
-        let data = fs::read_to_string(data_dir);
+        let data = read_input(self.day);
         match data {
             Ok(d) => match (self.day, self.part) {
                 // uncomment after running ./makeday.sh
@@ -73,10 +77,49 @@ impl SolutionRunner {
                     "day {day} part {part} should have been excluded from command line input"
                 ),
             },
-            Err(e) => Err(error::Error::FileNotFound(e)),
+            Err(e) => Err(e),
         }
     }
     pub fn new(day: u8, part: u8) -> SolutionRunner {
         SolutionRunner { day, part }
     }
 }
+
+/// Runs the debugging tools for a day against the users input, passing along any extra
+/// command line arguments
+#[derive(Debug, Clone)]
+pub struct DebugRunner {
+    day: u8,
+    args: Vec<String>,
+}
+
+impl DebugRunner {
+    pub fn debug(&self) -> Result<String, error::Error> {
+        // days that can stream their input from stdin don't need it in data/
+        let d = match (self.day, self.args.iter().any(|a| a == "--stdin")) {
+            (3, true) => String::new(),
+            (_, true) => return Err(error::Error::InvalidArgument("--stdin".to_string())),
+            (_, false) => read_input(self.day)?,
+        };
+        match self.day {
+            2 => days::day02::debug(d, &self.args),
+            3 => days::day03::debug(d, &self.args),
+            5 => days::day05::debug(d, &self.args),
+            6 => days::day06::debug(d, &self.args),
+            7 => days::day07::debug(d, &self.args),
+            8 => days::day08::debug(d, &self.args),
+            9 => days::day09::debug(d, &self.args),
+            10 => days::day10::debug(d, &self.args),
+            11 => days::day11::debug(d, &self.args),
+            13 => days::day13::debug(d, &self.args),
+            14 => days::day14::debug(d, &self.args),
+            17 => days::day17::debug(d, &self.args),
+            21 => days::day21::debug(d, &self.args),
+            24 => days::day24::debug(d, &self.args),
+            _ => Err(error::Error::DebugNotExists),
+        }
+    }
+    pub fn new(day: u8, args: Vec<String>) -> DebugRunner {
+        DebugRunner { day, args }
+    }
+}
@@ -15,6 +15,66 @@ pub enum ArrayOrientation {
     Full,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North, // : ^
+    East,  // : >
+    South, // : v
+    West,  // : <
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
+    pub fn right_turn(&self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+    pub fn left_turn(&self) -> Direction {
+        self.right_turn().opposite()
+    }
+    pub fn opposite(&self) -> Direction {
+        self.right_turn().right_turn()
+    }
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+}
+
+pub fn grid_from_input(input: &str) -> Array2D<char> {
+    Array2D::from_rows(
+        &input
+            .lines()
+            .map(|ln| ln.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>(),
+    )
+    .unwrap()
+}
+
+pub fn find_in_grid(arr: &Array2D<char>, target: char) -> Option<(usize, usize)> {
+    arr.enumerate_row_major()
+        .find(|(_, c)| **c == target)
+        .map(|(p, _)| p)
+}
+
 pub struct ArrayUtils2D {
     height: usize,
     width: usize,
@@ -26,6 +86,9 @@ impl ArrayUtils2D {
         let width = a.num_columns();
         ArrayUtils2D { height, width }
     }
+    pub fn new(height: usize, width: usize) -> ArrayUtils2D {
+        ArrayUtils2D { height, width }
+    }
 
     pub fn vertical_range(&self) -> Range<usize> {
         0..self.height
@@ -76,6 +139,15 @@ impl ArrayUtils2D {
         }
     }
 
+    pub fn step(&self, point: (usize, usize), d: Direction) -> Option<(usize, usize)> {
+        match d {
+            Direction::North => self.subtract_option(point.0, 1).map(|i| (i, point.1)),
+            Direction::East => self.as_index_tuple(point.0, point.1 + 1),
+            Direction::South => self.as_index_tuple(point.0 + 1, point.1),
+            Direction::West => self.subtract_option(point.1, 1).map(|j| (point.0, j)),
+        }
+    }
+
     pub fn as_index_tuple(&self, i: usize, j: usize) -> Option<(usize, usize)> {
         if self.in_limits(i, j) {
             Some((i, j))
@@ -170,6 +242,18 @@ pub fn demo() -> () {
 mod tests {
     use super::*;
     #[test]
+    fn step_test() {
+        let a = ArrayUtils2D::new(3, 4);
+        assert_eq!(a.step((0, 0), Direction::North), None);
+        assert_eq!(a.step((0, 0), Direction::West), None);
+        assert_eq!(a.step((2, 3), Direction::South), None);
+        assert_eq!(a.step((2, 3), Direction::East), None);
+        assert_eq!(a.step((1, 1), Direction::North), Some((0, 1)));
+        assert_eq!(a.step((1, 1), Direction::East), Some((1, 2)));
+        assert_eq!(a.step((1, 1), Direction::South), Some((2, 1)));
+        assert_eq!(a.step((1, 1), Direction::West), Some((1, 0)));
+    }
+    #[test]
     fn negative_diagonal_test() {
         let mut pre_transform: Array2D<usize> = Array2D::from_rows(&[
             vec![0, 0, 0, 0, 0],
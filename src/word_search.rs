@@ -0,0 +1,280 @@
+use array2d::Array2D;
+
+use crate::utils::{ArrayUtils2D, Gradient};
+
+/// The way a word reads across the grid from its first letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Heading {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Heading {
+    /// the heading of a line from `ArrayUtils2D::lines`, read in the order its points are given
+    fn along(g: &Gradient) -> Heading {
+        match g {
+            Gradient::Horizontal => Heading::East,
+            Gradient::PositiveDiagonal => Heading::NorthEast,
+            Gradient::Vertical => Heading::South,
+            Gradient::NegativeDiagonal => Heading::SouthEast,
+        }
+    }
+
+    pub fn opposite(&self) -> Heading {
+        match self {
+            Heading::North => Heading::South,
+            Heading::NorthEast => Heading::SouthWest,
+            Heading::East => Heading::West,
+            Heading::SouthEast => Heading::NorthWest,
+            Heading::South => Heading::North,
+            Heading::SouthWest => Heading::NorthEast,
+            Heading::West => Heading::East,
+            Heading::NorthWest => Heading::SouthEast,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence<'a> {
+    pub word: &'a str,
+    /// where the first letter of the word is
+    pub start: (usize, usize),
+    pub heading: Heading,
+}
+
+/// A 2D pattern of letters where `.` matches anything, e.g. the X-MAS cross:
+/// ```text
+/// M.S
+/// .A.
+/// M.S
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stencil {
+    height: usize,
+    width: usize,
+    /// the letters that have to match as (row, col) offsets from the top left
+    cells: Vec<((usize, usize), char)>,
+}
+
+impl Stencil {
+    pub fn from_pattern(pattern: &str) -> Stencil {
+        let rows: Vec<&str> = pattern.lines().collect();
+        let cells = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(i, r)| r.chars().enumerate().map(move |(j, c)| ((i, j), c)))
+            .filter(|(_, c)| *c != '.')
+            .collect();
+        Stencil {
+            height: rows.len(),
+            width: rows.iter().map(|r| r.chars().count()).max().unwrap_or(0),
+            cells,
+        }
+    }
+
+    fn transformed(
+        &self,
+        height: usize,
+        width: usize,
+        f: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Stencil {
+        let mut cells: Vec<((usize, usize), char)> = self
+            .cells
+            .iter()
+            .map(|((i, j), c)| (f(*i, *j), *c))
+            .collect();
+        cells.sort();
+        Stencil {
+            height,
+            width,
+            cells,
+        }
+    }
+
+    /// a quarter turn clockwise
+    pub fn rotate(&self) -> Stencil {
+        self.transformed(self.width, self.height, |i, j| (j, self.height - 1 - i))
+    }
+
+    /// mirrored left to right
+    pub fn reflect(&self) -> Stencil {
+        self.transformed(self.height, self.width, |i, j| (i, self.width - 1 - j))
+    }
+
+    /// Every distinct rotation and reflection of the stencil, symmetric stencils have fewer
+    /// than 8 so a match is never found twice in the same place.
+    pub fn orientations(&self) -> Vec<Stencil> {
+        let mut orientations: Vec<Stencil> = Vec::new();
+        let mut s = self.transformed(self.height, self.width, |i, j| (i, j));
+        for _ in 0..4 {
+            for o in [s.reflect(), s.clone()] {
+                if !orientations.contains(&o) {
+                    orientations.push(o);
+                }
+            }
+            s = s.rotate();
+        }
+        orientations
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StencilMatch {
+    pub top_left: (usize, usize),
+    /// the rotation or reflection of the stencil that matched
+    pub orientation: Stencil,
+}
+
+/// A grid of letters to search for words in any of the 8 headings, or for stencils.
+pub struct WordSearch {
+    grid: Array2D<char>,
+    limits: ArrayUtils2D,
+}
+
+impl WordSearch {
+    pub fn new(grid: Array2D<char>) -> WordSearch {
+        let limits = ArrayUtils2D::from_array(&grid);
+        WordSearch { grid, limits }
+    }
+
+    /// Every occurrence of each word, occurrences are allowed to overlap and share letters.
+    /// A palindrome is found once reading each way, but a single letter is only found once
+    /// per cell, heading East.
+    pub fn find_words<'a>(&self, words: &[&'a str]) -> Vec<Occurrence<'a>> {
+        let words: Vec<(&str, Vec<char>)> =
+            words.iter().map(|w| (*w, w.chars().collect())).collect();
+        let mut found = Vec::new();
+        for g in [
+            Gradient::Horizontal,
+            Gradient::PositiveDiagonal,
+            Gradient::Vertical,
+            Gradient::NegativeDiagonal,
+        ] {
+            let heading = Heading::along(&g);
+            let horizontal = matches!(g, Gradient::Horizontal);
+            for line in self.limits.lines(g) {
+                let letters: Vec<char> = line.iter().map(|p| self.grid[*p]).collect();
+                for (word, chars) in &words {
+                    if chars.is_empty() || (chars.len() == 1 && !horizontal) {
+                        continue;
+                    }
+                    for (k, window) in letters.windows(chars.len()).enumerate() {
+                        if window == chars.as_slice() {
+                            found.push(Occurrence {
+                                word,
+                                start: line[k],
+                                heading,
+                            });
+                        }
+                        if chars.len() > 1 && window.iter().rev().eq(chars.iter()) {
+                            found.push(Occurrence {
+                                word,
+                                start: line[k + chars.len() - 1],
+                                heading: heading.opposite(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Every placement of the stencil, or of any of its distinct rotations and reflections
+    /// when `orientations` is set, where all its letters match the grid.
+    pub fn find_stencil(&self, stencil: &Stencil, orientations: bool) -> Vec<StencilMatch> {
+        let stencils = match orientations {
+            true => stencil.orientations(),
+            false => vec![stencil.clone()],
+        };
+        let mut found = Vec::new();
+        for s in stencils {
+            for top_left in self.limits.all_points() {
+                let (i, j) = top_left;
+                let fits = s.height > 0
+                    && s.width > 0
+                    && self.limits.in_limits(i + s.height - 1, j + s.width - 1)
+                    && s.cells
+                        .iter()
+                        .all(|((di, dj), c)| self.grid[(i + di, j + dj)] == *c);
+                if fits {
+                    found.push(StencilMatch {
+                        top_left,
+                        orientation: s.clone(),
+                    });
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::grid_from_input;
+    #[test]
+    fn find_words_test() {
+        let search = WordSearch::new(grid_from_input("..X...\n.SAMX.\n.A..A.\nXMAS.S\n.X...."));
+        let mut found = search.find_words(&["XMAS"]);
+        found.sort_by_key(|o| (o.start, o.heading));
+        let found: Vec<((usize, usize), Heading)> =
+            found.iter().map(|o| (o.start, o.heading)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ((0, 2), Heading::SouthEast),
+                ((1, 4), Heading::West),
+                ((3, 0), Heading::East),
+                ((4, 1), Heading::North),
+            ]
+        );
+    }
+    #[test]
+    fn overlapping_words_test() {
+        let search = WordSearch::new(grid_from_input("AAA"));
+        assert_eq!(search.find_words(&["AA"]).len(), 4);
+        assert_eq!(search.find_words(&["ABA", "B"]).len(), 0);
+    }
+    #[test]
+    fn single_letter_test() {
+        let search = WordSearch::new(grid_from_input("...\n.X.\n..."));
+        let found = search.find_words(&["X"]);
+        assert_eq!(
+            found,
+            vec![Occurrence {
+                word: "X",
+                start: (1, 1),
+                heading: Heading::East
+            }]
+        );
+        assert_eq!(search.find_words(&["X", "."]).len(), 9);
+    }
+    #[test]
+    fn orientations_test() {
+        let x_mas = Stencil::from_pattern("M.S\n.A.\nM.S");
+        assert_eq!(x_mas.orientations().len(), 4);
+        let mirrored = Stencil::from_pattern("A.\nBA");
+        assert_eq!(mirrored.orientations().len(), 4);
+        let asymmetric = Stencil::from_pattern("AB\nC.");
+        assert_eq!(asymmetric.orientations().len(), 8);
+        assert_eq!(asymmetric.rotate(), Stencil::from_pattern("CA\n.B"));
+        assert_eq!(asymmetric.reflect(), Stencil::from_pattern("BA\n.C"));
+    }
+    #[test]
+    fn find_stencil_test() {
+        let search = WordSearch::new(grid_from_input("S.M.\n.A..\nS.M.\n...."));
+        let x_mas = Stencil::from_pattern("M.S\n.A.\nM.S");
+        assert!(search.find_stencil(&x_mas, false).is_empty());
+        let found = search.find_stencil(&x_mas, true);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].top_left, (0, 0));
+        assert_eq!(found[0].orientation, Stencil::from_pattern("S.M\n.A.\nS.M"));
+    }
+}